@@ -0,0 +1,215 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::fs::{create_dir_all, read_to_string, write};
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::{collect::FileKeys, config::Target, error::Error};
+
+pub const CACHE_DIR: &str = ".t-cli/cache";
+
+/// Keys extracted per file, reused while the file's content, the target's config and the
+/// t-cli version are unchanged. Stored in `.t-cli/cache/target-{index}.json`.
+pub struct Cache {
+    path: Option<PathBuf>,
+    config: u64,
+    entries: HashMap<PathBuf, CacheEntry>,
+    /// Entries of this run, so files that no longer exist are dropped on save
+    updated: Mutex<HashMap<PathBuf, CacheEntry>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: String,
+    config: u64,
+    files: HashMap<PathBuf, CacheEntry>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    hash: u64,
+    keys: FileKeys,
+}
+
+impl Cache {
+    /// A cache that is never read nor written.
+    pub fn disabled() -> Self {
+        Self {
+            path: None,
+            config: 0,
+            entries: HashMap::new(),
+            updated: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Load the cache of the `index`-th target, empty when missing, outdated or invalid.
+    pub async fn load(index: usize, target: &Target) -> Result<Self, Error> {
+        let path = Path::new(CACHE_DIR).join(format!("target-{index}.json"));
+        Ok(Self::open(path, config_hash(target)?).await)
+    }
+
+    /// The cache stored at `path`, keeping its entries only when written by this version
+    /// for the same `config`.
+    async fn open(path: PathBuf, config: u64) -> Self {
+        let entries = read_to_string(&path)
+            .await
+            .ok()
+            .and_then(|content| serde_json::from_str::<CacheFile>(&content).ok())
+            .filter(|file| file.version == env!("CARGO_PKG_VERSION") && file.config == config)
+            .map(|file| file.files)
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            config,
+            entries,
+            updated: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Keys of `path` when its content hashes to `hash` as in the cached run.
+    pub fn get(&self, path: &Path, hash: u64) -> Option<FileKeys> {
+        let entry = self.entries.get(path).filter(|entry| entry.hash == hash)?;
+        self.updated
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), entry.clone());
+        Some(entry.keys.clone())
+    }
+
+    pub fn insert(&self, path: &Path, hash: u64, keys: &FileKeys) {
+        if self.path.is_none() {
+            return;
+        }
+        let entry = CacheEntry {
+            hash,
+            keys: keys.clone(),
+        };
+        self.updated
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), entry);
+    }
+
+    /// Write the entries of this run, dropping files that weren't scanned.
+    pub async fn save(&self) -> Result<(), Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = CacheFile {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            config: self.config,
+            files: std::mem::take(&mut *self.updated.lock().unwrap()),
+        };
+        let json = serde_json::to_string(&file)?;
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).await?;
+        }
+        write(path, json).await?;
+        Ok(())
+    }
+}
+
+/// Hash of a target's config, e.g. its `fnNames`, which decides what a file's keys are.
+fn config_hash(target: &Target) -> Result<u64, Error> {
+    Ok(xxh3_64(serde_json::to_string(target)?.as_bytes()))
+}
+
+pub fn content_hash(content: &[u8]) -> u64 {
+    xxh3_64(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collect::Location;
+
+    use super::*;
+
+    fn target(fn_names: &[&str]) -> Target {
+        serde_json::from_value(serde_json::json!({
+            "includes": [],
+            "excludes": [],
+            "fnNames": fn_names,
+        }))
+        .unwrap()
+    }
+
+    /// Keys told apart by the line of their single dynamic key.
+    fn keys(line: usize) -> FileKeys {
+        FileKeys {
+            keys: vec![],
+            dynamic: vec![Location { line, col: 1 }],
+        }
+    }
+
+    fn line(cache: &Cache, path: &str, content: &str) -> Option<usize> {
+        let keys = cache.get(Path::new(path), content_hash(content.as_bytes()))?;
+        Some(keys.dynamic[0].line)
+    }
+
+    #[tokio::test]
+    async fn hit_only_on_unchanged_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("target-0.json");
+        let cache = Cache::open(path.clone(), 1).await;
+        assert_eq!(line(&cache, "a.ts", "t('a')"), None);
+        cache.insert(Path::new("a.ts"), content_hash(b"t('a')"), &keys(1));
+        cache.save().await.unwrap();
+
+        let cache = Cache::open(path, 1).await;
+        assert_eq!(line(&cache, "a.ts", "t('a')"), Some(1));
+        assert_eq!(line(&cache, "a.ts", "t('b')"), None);
+    }
+
+    #[tokio::test]
+    async fn config_or_version_change_invalidates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("target-0.json");
+        let config = config_hash(&target(&["t"])).unwrap();
+        let cache = Cache::open(path.clone(), config).await;
+        cache.insert(Path::new("a.ts"), content_hash(b"t('a')"), &keys(1));
+        cache.save().await.unwrap();
+
+        let changed = config_hash(&target(&["t", "i18n.t"])).unwrap();
+        assert_ne!(config, changed);
+        assert_eq!(
+            line(&Cache::open(path.clone(), changed).await, "a.ts", "t('a')"),
+            None
+        );
+        assert_eq!(
+            line(&Cache::open(path.clone(), config).await, "a.ts", "t('a')"),
+            Some(1)
+        );
+
+        let mut file: CacheFile =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        file.version = "0.0.0".to_string();
+        std::fs::write(&path, serde_json::to_string(&file).unwrap()).unwrap();
+        assert_eq!(
+            line(&Cache::open(path, config).await, "a.ts", "t('a')"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn save_prunes_files_not_scanned() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("target-0.json");
+        let cache = Cache::open(path.clone(), 1).await;
+        cache.insert(Path::new("a.ts"), content_hash(b"a"), &keys(1));
+        cache.insert(Path::new("b.ts"), content_hash(b"b"), &keys(2));
+        cache.save().await.unwrap();
+
+        // b.ts was deleted, so only a.ts is looked up
+        let cache = Cache::open(path.clone(), 1).await;
+        assert_eq!(line(&cache, "a.ts", "a"), Some(1));
+        cache.save().await.unwrap();
+
+        let cache = Cache::open(path, 1).await;
+        assert_eq!(line(&cache, "a.ts", "a"), Some(1));
+        assert_eq!(line(&cache, "b.ts", "b"), None);
+    }
+}
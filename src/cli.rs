@@ -1,4 +1,6 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+
+use crate::collect::ScanOptions;
 
 #[derive(Parser)]
 #[command(name = "t-cli")]
@@ -24,6 +26,8 @@ pub enum Commands {
         config: String,
         #[arg(long, default_value_t = false)]
         verbose: bool,
+        #[command(flatten)]
+        collect: CollectArgs,
     },
 
     #[command(visible_alias = "g")]
@@ -39,21 +43,63 @@ pub enum Commands {
         config: String,
         #[arg(long, default_value_t = false)]
         verbose: bool,
+        #[command(flatten)]
+        scan: ScanArgs,
     },
 
-    /// Collect + Generate (equivalent to: t-cli collect && t-cli generate)
+    /// Collect + Generate in one pass (equivalent to: t-cli collect && t-cli generate)
     Cg {
         #[arg(short, long, default_value = "t.config.ts")]
         config: String,
         #[arg(long, default_value_t = false)]
         verbose: bool,
+        #[command(flatten)]
+        collect: CollectArgs,
     },
 
-    /// Collect + Generate + Clean (equivalent to: t-cli collect && t-cli generate && t-cli clean)
+    /// Collect + Clean + Generate in one pass (equivalent to: t-cli collect && t-cli clean && t-cli generate)
     Gc {
         #[arg(short, long, default_value = "t.config.ts")]
         config: String,
         #[arg(long, default_value_t = false)]
         verbose: bool,
+        #[command(flatten)]
+        collect: CollectArgs,
     },
 }
+
+/// Options of the commands that scan source files.
+#[derive(Args)]
+pub struct ScanArgs {
+    /// Skip files that fail to parse and report them at the end instead of stopping
+    #[arg(long, default_value_t = false)]
+    pub keep_going: bool,
+    /// Number of files parsed in parallel, defaults to the number of CPUs
+    #[arg(short, long)]
+    pub jobs: Option<usize>,
+    /// Parse every file instead of reusing keys cached in `.t-cli/cache`
+    #[arg(long, default_value_t = false)]
+    pub no_cache: bool,
+}
+
+/// Options of the commands that collect keys.
+#[derive(Args)]
+pub struct CollectArgs {
+    #[command(flatten)]
+    pub scan: ScanArgs,
+    /// Fail when a translation function is called with a key that can't be extracted
+    #[arg(long, default_value_t = false)]
+    pub deny_dynamic: bool,
+}
+
+impl From<ScanArgs> for ScanOptions {
+    fn from(args: ScanArgs) -> Self {
+        Self {
+            keep_going: args.keep_going,
+            jobs: args
+                .jobs
+                .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get())),
+            cache: !args.no_cache,
+        }
+    }
+}
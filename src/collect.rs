@@ -1,82 +1,303 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet, hash_map::Entry},
+    path::{Path, PathBuf},
+    pin::pin,
+    sync::Arc,
+};
 
+use futures::{Stream, StreamExt, stream};
 use indexmap::IndexMap;
-use swc_common::{FileName, SourceMap, sync::Lrc};
-use swc_ecma_ast::{Callee, Program};
-use swc_ecma_parser::{Parser, StringInput, Syntax, TsSyntax};
-use swc_ecma_visit::{Visit, VisitWith};
-use tokio::fs::{create_dir_all, read_to_string, write};
-use walkdir::WalkDir;
+use serde::{Deserialize, Serialize};
+use swc_common::{
+    BytePos, FileName, GLOBALS, Globals, Mark, SourceMap, Span, Spanned,
+    comments::{Comment, Comments, SingleThreadedComments},
+    sync::Lrc,
+};
+use swc_ecma_ast::{
+    CallExpr, Callee, ClassMember, Decl, ExportDecl, Expr, ExprOrSpread, JSXAttr, JSXAttrName,
+    JSXAttrOrSpread, JSXAttrValue, JSXElement, JSXElementChild, JSXExpr, JSXExprContainer, Lit,
+    MemberExpr, MemberProp, Module, ModuleDecl, ModuleItem, OptCall, OptChainBase, Pat, Program,
+    Prop, PropOrSpread, Stmt, TaggedTpl, Tpl, VarDeclKind,
+};
+use swc_ecma_parser::{Parser, StringInput};
+use swc_ecma_transforms_base::resolver;
+use swc_ecma_visit::{Visit, VisitMutWith, VisitWith};
+use tokio::task::spawn_blocking;
 
 use crate::{
-    config::Config,
+    cache::{Cache, content_hash},
+    config::{Component, Config, Descriptors, FnName, ImportSpec, Target},
+    dictionaries::{Dictionaries, Dictionary, DictionaryFile},
     error::Error,
-    utils::{is_target_file::is_target_file, resolve::resolve_workspaces},
+    utils::{
+        diagnostic::render_parse_error,
+        directives::Directives,
+        expr_to_keys::{ConstMap, expr_to_key, expr_to_keys, object_lit, object_prop, tpl_to_keys},
+        extractors::{Extractors, FileExtractor, extract_content, extract_matches},
+        file_filter::FileFilter,
+        import_bindings::ImportBindings,
+        is_target_file::is_target_file,
+        jsx_text::{clean_jsx_text, jsx_element_name},
+        resolve::{Workspace, ancestors, resolve_workspaces, walk_builder},
+        sfc::sfc_to_script,
+        syntax::{syntax_for_path, syntax_with_defaults},
+    },
 };
 
-pub async fn run_collect(config: Config, verbose: bool) -> Result<(), Error> {
-    let cm: Lrc<SourceMap> = Default::default();
-    let mut collected: HashMap<PathBuf, IndexMap<String, Option<String>>> = HashMap::new();
-    let default_lang = config.languages.name.clone();
-    let all_langs = config.languages.collect_languages();
+/// How `collect` and `clean` scan source files.
+pub struct ScanOptions {
+    /// Skip files that fail to parse, returning their diagnostics
+    pub keep_going: bool,
+    /// Files parsed at once
+    pub jobs: usize,
+    /// Reuse keys of unchanged files from `.t-cli/cache`
+    pub cache: bool,
+}
+
+/// Keys of every file of one target, extracted once and shared by `collect` and `clean`.
+pub struct TargetScan {
+    pub target: Target,
+    pub workspaces: Vec<Workspace>,
+    /// Files of each workspace in order, without those that failed to parse
+    pub files: Vec<Vec<(PathBuf, FileKeys)>>,
+    /// Output directories of workspaces where a file failed to parse
+    pub unknown: HashSet<PathBuf>,
+}
+
+/// Scanned targets and diagnostics of the files skipped with `keep_going`.
+pub struct Scan {
+    pub targets: Vec<TargetScan>,
+    pub parse_errors: Vec<String>,
+}
+
+/// Extract the keys of every target's files, in workspace a–z (case-insensitive) and file a–z
+/// (case-insensitive) order.
+pub async fn scan(config: &Config, verbose: bool, options: &ScanOptions) -> Result<Scan, Error> {
+    let outputs = config.outputs();
+    let mut targets = vec![];
+    let mut parse_errors = vec![];
 
-    for target in config.targets {
-        let workspaces = resolve_workspaces(".", &target.includes, &target.excludes)?;
-        for workspace in workspaces {
+    for (index, target) in config.targets.iter().enumerate() {
+        let cache = Arc::new(if options.cache {
+            Cache::load(index, target).await?
+        } else {
+            Cache::disabled()
+        });
+        let filter = FileFilter::new(target)?;
+        let extractors = Arc::new(Extractors::new(&target.extractors, &target.content_files)?);
+        let workspaces = resolve_workspaces(".", target, &outputs)?;
+        let paths: Vec<Vec<PathBuf>> = workspaces
+            .iter()
+            .map(|workspace| workspace_files(workspace, &filter, &extractors))
+            .collect();
+        // Parsed ahead on the worker pool, consumed in workspace and file order
+        let mut results = pin!(collect_files(
+            paths.iter().flatten().cloned().collect(),
+            Arc::new(target.clone()),
+            extractors,
+            cache.clone(),
+            options.jobs,
+        ));
+
+        let mut files = vec![];
+        let mut unknown = HashSet::new();
+        for (workspace, paths) in workspaces.iter().zip(&paths) {
             if verbose {
-                println!("Scanning workspace: {}", workspace.display());
+                println!("Scanning workspace: {}", workspace.dir.display());
+            }
+            // Output directories aren't scanned, so code written there is never collected
+            for path in &workspace.output_sources {
+                println!(
+                    "⚠️ {}: source file inside output directory is not scanned",
+                    path.display()
+                );
             }
+            // Left behind by another scope, e.g. after switching from `directory`
+            for dir in &workspace.dirs {
+                let output_dir = dir.join(&target.output);
+                if dir != &workspace.dir && output_dir.is_dir() {
+                    println!(
+                        "⚠️ {}: not a workspace, its keys are collected into {}",
+                        output_dir.display(),
+                        workspace.dir.join(&target.output).display()
+                    );
+                }
+            }
+            let mut workspace_files = vec![];
+            for _ in paths {
+                let Some((path, result)) = results.next().await else {
+                    break;
+                };
+                match result {
+                    Ok(file) => workspace_files.push((path, file)),
+                    Err(Error::ParseModule(diagnostic)) if options.keep_going => {
+                        println!("⚠️ Skipped {}: failed to parse", path.display());
+                        parse_errors.push(diagnostic);
+                        unknown.insert(workspace.dir.join(&target.output));
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            files.push(workspace_files);
+        }
+        cache.save().await?;
 
-            // Collect one level of files and sort by file name a-z
-            let mut files: Vec<PathBuf> = WalkDir::new(&workspace)
-                .max_depth(1)
-                .into_iter()
-                .filter_map(Result::ok)
-                .map(|e| e.into_path())
-                .filter(|p| p.is_file() && is_target_file(p))
-                .collect();
+        targets.push(TargetScan {
+            target: target.clone(),
+            workspaces,
+            files,
+            unknown,
+        });
+    }
 
-            files.sort_by(|a, b| {
-                a.to_string_lossy()
-                    .to_lowercase()
-                    .cmp(&b.to_string_lossy().to_lowercase())
-            });
+    Ok(Scan {
+        targets,
+        parse_errors,
+    })
+}
 
-            for path in files {
-                let content = read_to_string(&path).await?;
-                let fm =
-                    cm.new_source_file(FileName::Real(path.to_path_buf()).into(), content.clone());
-
-                let mut parser = Parser::new(
-                    Syntax::Typescript(TsSyntax {
-                        tsx: true,
-                        decorators: true,
-                        ..Default::default()
-                    }),
-                    StringInput::from(&*fm),
-                    None,
-                );
+/// Collect keys into each workspace's `{output}/{lang}.json`. Diagnostics of files skipped
+/// with `keep_going` are returned.
+pub async fn run_collect(
+    config: Config,
+    verbose: bool,
+    deny_dynamic: bool,
+    options: &ScanOptions,
+) -> Result<Vec<String>, Error> {
+    let scan = scan(&config, verbose, options).await?;
+    let mut dictionaries = Dictionaries::default();
+    merge_collected(&config, &scan.targets, &mut dictionaries, deny_dynamic).await?;
+    dictionaries.save().await?;
+    Ok(scan.parse_errors)
+}
 
-                let module = parser.parse_module().map_err(Error::ParseModule)?;
+/// Add scanned keys to the dictionaries: keys in scan order, then the existing keys that
+/// weren't found, keeping existing translations. Fails before changing anything with
+/// `deny_dynamic` when a key can't be extracted.
+pub async fn merge_collected(
+    config: &Config,
+    targets: &[TargetScan],
+    dictionaries: &mut Dictionaries,
+    deny_dynamic: bool,
+) -> Result<(), Error> {
+    let mut collected: HashMap<PathBuf, Dictionary> = HashMap::new();
+    // Default-language keys whose value comes from a default message in code, per JSON file
+    let mut from_code: HashMap<PathBuf, HashSet<String>> = HashMap::new();
+    let mut metas: HashMap<PathBuf, IndexMap<String, MessageMeta>> = HashMap::new();
+    // First default message seen per (output dir, key), with its location
+    let mut defaults: HashMap<(PathBuf, String), (String, String)> = HashMap::new();
+    let default_lang = config.languages.name.clone();
+    let all_langs = config.languages.collect_languages();
+    let mut dynamic_count = 0usize;
 
-                let mut visitor = FnKeyCollector {
-                    keys: vec![],
-                    fn_names: target.fn_names.clone(),
-                };
+    for TargetScan {
+        target,
+        workspaces,
+        files,
+        ..
+    } in targets
+    {
+        for (workspace, files) in workspaces.iter().zip(files) {
+            // Keys an ancestor's dictionary already defines aren't repeated in this one
+            let mut inherited = HashSet::new();
+            if target.inherit {
+                for ancestor in ancestors(workspaces, &workspace.dir) {
+                    let file_path = ancestor
+                        .dir
+                        .join(&target.output)
+                        .join(format!("{default_lang}.json"));
+                    if let Some(map) = collected.get(&file_path) {
+                        inherited.extend(map.keys().cloned());
+                    }
+                    inherited.extend(dictionaries.keys(&file_path).await?);
+                }
+            }
 
-                let program = Program::Module(module);
-                program.visit_with(&mut visitor);
+            let output_dir = workspace.dir.join(&target.output);
+            for (path, file) in files {
+                let separator = &target.context_separator;
+                let keys: Vec<&Message> = file
+                    .keys
+                    .iter()
+                    .filter(|message| !inherited.contains(&message.id(separator)))
+                    .collect();
+
+                for location in &file.dynamic {
+                    println!(
+                        "⚠️ {}:{}:{}: dynamic key can't be extracted",
+                        path.display(),
+                        location.line,
+                        location.col
+                    );
+                }
+                dynamic_count += file.dynamic.len();
+
+                for message in &keys {
+                    let Some(default) = &message.default else {
+                        continue;
+                    };
+                    let location = format!(
+                        "{}:{}:{}",
+                        path.display(),
+                        message.location.line,
+                        message.location.col
+                    );
+                    match defaults.entry((output_dir.clone(), message.id(separator))) {
+                        Entry::Occupied(first) if &first.get().0 != default => {
+                            println!(
+                                "⚠️ {location}: conflicting default for \"{}\": \"{default}\" (first defined as \"{}\" at {})",
+                                message.id(separator).replace(separator, " | "),
+                                first.get().0,
+                                first.get().1
+                            );
+                        }
+                        Entry::Occupied(_) => {}
+                        Entry::Vacant(entry) => {
+                            entry.insert((default.clone(), location));
+                        }
+                    }
+                }
+
+                for message in &keys {
+                    if message.description.is_none() && message.comment.is_none() {
+                        continue;
+                    }
+                    let meta = metas
+                        .entry(output_dir.join(META_FILE))
+                        .or_default()
+                        .entry(message.id(separator))
+                        .or_default();
+                    if let Some(description) = &message.description {
+                        meta.description.get_or_insert_with(|| description.clone());
+                    }
+                    if let Some(comment) = &message.comment
+                        && !meta.comments.contains(comment)
+                    {
+                        meta.comments.push(comment.clone());
+                    }
+                }
 
-                if !visitor.keys.is_empty() {
-                    let output_dir = workspace.join(&target.output);
+                if !keys.is_empty() {
                     for lang in &all_langs {
-                        let map = collected
-                            .entry(output_dir.join(format!("{lang}.json")))
-                            .or_default();
-                        for k in &visitor.keys {
+                        let file_path = output_dir.join(format!("{lang}.json"));
+                        let map = collected.entry(file_path.clone()).or_default();
+                        for message in &keys {
+                            let k = &message.id(separator);
                             if lang == &default_lang {
-                                // Default language: key => same value
-                                map.insert(k.clone(), Some(k.clone()));
+                                // Default language: key => default message (first one wins) or key itself
+                                let default = defaults
+                                    .get(&(output_dir.clone(), k.clone()))
+                                    .map(|(default, _)| default.clone());
+                                if default.is_some() {
+                                    from_code
+                                        .entry(file_path.clone())
+                                        .or_default()
+                                        .insert(k.clone());
+                                }
+                                map.insert(
+                                    k.clone(),
+                                    Some(default.unwrap_or_else(|| message.key.clone())),
+                                );
                             } else {
                                 // Other languages: only placeholder, keep insertion order
                                 map.entry(k.clone()).or_insert(None);
@@ -88,57 +309,1047 @@ pub async fn run_collect(config: Config, verbose: bool) -> Result<(), Error> {
         }
     }
 
+    if deny_dynamic && dynamic_count > 0 {
+        return Err(Error::Error(format!(
+            "Found {dynamic_count} dynamic key(s) that can't be extracted"
+        )));
+    }
+
     for (file_path, mut map) in collected {
-        if file_path.exists() {
-            let old_content = read_to_string(&file_path).await?;
-            // Use IndexMap to read old file, preserve order semantics
-            if let Ok(old_map) =
-                serde_json::from_str::<IndexMap<String, Option<String>>>(&old_content)
-            {
-                for (k, v) in old_map {
-                    if map.contains_key(&k) {
-                        // Already exists: only override when old value is Some
-                        if let Some(val) = v {
-                            map.insert(k, Some(val));
-                        }
-                    } else {
-                        // Not exists: append to the end regardless of Some or None
-                        map.insert(k, v);
+        let empty = HashSet::new();
+        let from_code = from_code.get(&file_path).unwrap_or(&empty);
+        // Old file order is kept for keys not seen in this run
+        if let DictionaryFile::Valid(old_map) = dictionaries.dictionary(&file_path).await? {
+            for (k, v) in old_map {
+                if map.contains_key(k) {
+                    // Already exists: only override when old value is Some,
+                    // default messages in code always win
+                    if let Some(val) = v
+                        && !from_code.contains(k)
+                    {
+                        map.insert(k.clone(), Some(val.clone()));
                     }
+                } else {
+                    // Not exists: append to the end regardless of Some or None
+                    map.insert(k.clone(), v.clone());
                 }
             }
         }
+        dictionaries.set_dictionary(&file_path, map);
+    }
 
-        if let Some(parent) = file_path.parent() {
-            create_dir_all(parent).await?;
+    for (meta_path, mut meta) in metas {
+        let Some(old_meta) = dictionaries.meta(&meta_path).await? else {
+            println!("⚠️ Skipped {}: invalid JSON", meta_path.display());
+            continue;
+        };
+        // Keep entries of keys not seen in this run, `clean` prunes them
+        for (k, v) in old_meta {
+            meta.entry(k.clone()).or_insert(v.clone());
         }
-        let json = serde_json::to_string_pretty(&map)?;
-        write(file_path, json).await?;
+        dictionaries.set_meta(&meta_path, meta);
     }
 
     Ok(())
 }
 
+/// Print diagnostics of files skipped with `--keep-going` and fail when there are any.
+pub fn report_parse_errors(diagnostics: &[String]) -> Result<(), Error> {
+    if diagnostics.is_empty() {
+        return Ok(());
+    }
+    for diagnostic in diagnostics {
+        eprintln!("{diagnostic}\n");
+    }
+    Err(Error::Error(format!(
+        "Failed to parse {} file(s)",
+        diagnostics.len()
+    )))
+}
+
+/// Keys found in one source file.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FileKeys {
+    pub keys: Vec<Message>,
+    /// Keys that can't be statically evaluated (parameters, calls, `${}` with unknown values...)
+    pub dynamic: Vec<Location>,
+}
+
+/// 1-based line and column in a source file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl Location {
+    fn lookup(cm: &SourceMap, pos: BytePos) -> Self {
+        let loc = cm.lookup_char_pos(pos);
+        Self {
+            line: loc.line,
+            col: loc.col_display + 1,
+        }
+    }
+}
+
+/// Extract `paths` on the blocking thread pool, at most `jobs` files at a time. Results are
+/// yielded in the order of `paths`.
+pub fn collect_files(
+    paths: Vec<PathBuf>,
+    target: Arc<Target>,
+    extractors: Arc<Extractors>,
+    cache: Arc<Cache>,
+    jobs: usize,
+) -> impl Stream<Item = (PathBuf, Result<FileKeys, Error>)> {
+    stream::iter(paths)
+        .map(move |path| {
+            let target = target.clone();
+            let extractors = extractors.clone();
+            let cache = cache.clone();
+            async move {
+                let task_path = path.clone();
+                let result = spawn_blocking(move || {
+                    collect_cached(&task_path, &target, &extractors, &cache)
+                })
+                .await
+                .unwrap_or_else(|e| Err(Error::Error(e.to_string())));
+                (path, result)
+            }
+        })
+        .buffered(jobs.max(1))
+}
+
+/// Files keys are extracted from in each directory of a workspace, in directory order.
+pub fn workspace_files(
+    workspace: &Workspace,
+    filter: &FileFilter,
+    extractors: &Extractors,
+) -> Vec<PathBuf> {
+    workspace
+        .dirs
+        .iter()
+        .flat_map(|dir| dir_files(dir, filter, extractors))
+        .collect()
+}
+
+/// Files of a directory (one level deep) that keys are extracted from, sorted by
+/// file name a-z (case-insensitive).
+fn dir_files(dir: &Path, filter: &FileFilter, extractors: &Extractors) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = walk_builder(dir, filter.respect_gitignore)
+        .max_depth(Some(1))
+        .build()
+        .filter_map(Result::ok)
+        .map(|e| e.into_path())
+        .filter(|p| {
+            p.is_file() && (is_target_file(p) || extractors.find(p).is_some()) && filter.is_match(p)
+        })
+        .collect();
+
+    files.sort_by(|a, b| {
+        a.to_string_lossy()
+            .to_lowercase()
+            .cmp(&b.to_string_lossy().to_lowercase())
+    });
+    files
+}
+
+/// [`collect_file`], skipping files whose content didn't change since the cached run.
+fn collect_cached(
+    path: &Path,
+    target: &Target,
+    extractors: &Extractors,
+    cache: &Cache,
+) -> Result<FileKeys, Error> {
+    let content = std::fs::read_to_string(path)?;
+    let hash = content_hash(content.as_bytes());
+    if let Some(keys) = cache.get(path, hash) {
+        return Ok(keys);
+    }
+    let keys = collect_file(path, content, target, extractors)?;
+    cache.insert(path, hash, &keys);
+    Ok(keys)
+}
+
+/// Extract the keys of a file: with the regex extractor matching its path, or by parsing it
+/// and running the key collector for `target` over it. Lines disabled by `t-cli-*` directives
+/// are left out.
+///
+/// Each file gets its own source map, dropped once locations are resolved.
+pub fn collect_file(
+    path: &Path,
+    content: String,
+    target: &Target,
+    extractors: &Extractors,
+) -> Result<FileKeys, Error> {
+    let cm: Lrc<SourceMap> = Default::default();
+
+    let (mut file, directives) = match extractors.find(path) {
+        Some(FileExtractor::Regex(patterns)) => {
+            let directives = Directives::from_lines(&content);
+            let fm = cm.new_source_file(FileName::Real(path.to_path_buf()).into(), content);
+            let keys = extract_matches(patterns, &fm.src)
+                .into_iter()
+                .map(|m| {
+                    let lo = fm.start_pos + BytePos(m.offset as u32);
+                    Message::new(m.key, m.context, Span::new(lo, lo))
+                })
+                .collect();
+            let file = FileKeys {
+                keys,
+                dynamic: vec![],
+            };
+            (file, directives)
+        }
+        Some(FileExtractor::Content(paths)) => {
+            let directives = Directives::from_lines(&content);
+            let keys = extract_content(path, &content, paths)?;
+            let fm = cm.new_source_file(FileName::Real(path.to_path_buf()).into(), content);
+            let span = Span::new(fm.start_pos, fm.start_pos);
+            let file = FileKeys {
+                keys: keys
+                    .into_iter()
+                    .map(|key| Message::new(key, None, span))
+                    .collect(),
+                dynamic: vec![],
+            };
+            (file, directives)
+        }
+        None => parse_file(&cm, path, content, target)?,
+    };
+
+    for message in &mut file.keys {
+        message.location = Location::lookup(&cm, message.span.lo);
+    }
+    if directives.ignore_file {
+        file.keys.clear();
+        file.dynamic.clear();
+    } else {
+        file.keys
+            .retain(|message| !directives.is_ignored(message.location.line));
+        file.dynamic
+            .retain(|location| !directives.is_ignored(location.line));
+    }
+    Ok(file)
+}
+
+/// Parse a JavaScript file (or single-file component) and run the key collector over it.
+fn parse_file(
+    cm: &Lrc<SourceMap>,
+    path: &Path,
+    content: String,
+    target: &Target,
+) -> Result<(FileKeys, Directives), Error> {
+    let (content, syntax, template_comments) = match sfc_to_script(path, &content) {
+        Some(sfc) => {
+            let syntax = syntax_with_defaults(path, sfc.typescript, sfc.jsx, &target.syntax);
+            (sfc.source, syntax, sfc.comments)
+        }
+        None => (content, syntax_for_path(path, &target.syntax), vec![]),
+    };
+    let fm = cm.new_source_file(FileName::Real(path.to_path_buf()).into(), content);
+
+    let comments = SingleThreadedComments::default();
+    let mut parser = Parser::new(syntax, StringInput::from(&*fm), Some(&comments));
+
+    let module = parser
+        .parse_module()
+        .map_err(|e| Error::ParseModule(render_parse_error(cm, e)))?;
+
+    let directives = {
+        let (leading, trailing) = comments.borrow_all();
+        let mut all: Vec<&Comment> = leading
+            .values()
+            .chain(trailing.values())
+            .flatten()
+            .collect();
+        all.sort_by_key(|comment| comment.span.lo);
+        // Template comments are blanked out of components, so they come from the original file
+        let mut all: Vec<(usize, &str)> = all
+            .into_iter()
+            .map(|comment| (cm.lookup_char_pos(comment.span.hi).line, &*comment.text))
+            .chain(
+                template_comments
+                    .iter()
+                    .map(|(line, text)| (*line, text.as_str())),
+            )
+            .collect();
+        all.sort_by_key(|&(line, _)| line);
+        Directives::from_comments(all)
+    };
+
+    let mut visitor = FnKeyCollector::new(target);
+    visitor.comments = comments;
+    let mut program = Program::Module(module);
+    GLOBALS.set(&Globals::new(), || {
+        // Give every binding a unique SyntaxContext so shadowed names can't match imports
+        // or module constants
+        program.visit_mut_with(&mut resolver(Mark::new(), Mark::new(), syntax.typescript()));
+        program.visit_with(&mut visitor);
+    });
+    let file = FileKeys {
+        keys: visitor.keys,
+        dynamic: visitor
+            .dynamic
+            .iter()
+            .map(|span| Location::lookup(cm, span.lo))
+            .collect(),
+    };
+    Ok((file, directives))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub key: String,
+    /// Default-language text given next to a stable ID, e.g. `t("id", "Default text")`
+    pub default: Option<String>,
+    /// Translator-facing description from a message descriptor
+    pub description: Option<String>,
+    /// Translator comment preceding the call, e.g. `// t: verb, opens the file dialog`
+    pub comment: Option<String>,
+    /// Disambiguates identical keys, e.g. `t("Order", { context: "verb" })`
+    pub context: Option<String>,
+    /// Span in the file's own source map
+    #[serde(skip)]
+    pub span: Span,
+    /// Resolved from `span` once the file is extracted
+    pub location: Location,
+}
+
+impl Message {
+    /// A bare key without default, description or comment.
+    fn new(key: String, context: Option<String>, span: Span) -> Self {
+        Self {
+            key,
+            default: None,
+            description: None,
+            comment: None,
+            context,
+            span,
+            location: Location::default(),
+        }
+    }
+
+    /// Dictionary key: the key itself, or `context + separator + key` with a context.
+    pub fn id(&self, separator: &str) -> String {
+        match &self.context {
+            Some(context) => format!("{context}{separator}{}", self.key),
+            None => self.key.clone(),
+        }
+    }
+}
+
+/// Translator-facing information about a key, stored in `{output}/meta.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MessageMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<String>,
+}
+
+pub const META_FILE: &str = "meta.json";
+
+/// File generated into each output directory by `generate`.
+pub const GENERATED_FILE: &str = "index.ts";
+
+/// Matches callees against configured function names.
+#[derive(Default)]
+struct FnMatcher {
+    paths: Vec<String>,
+    imports: Vec<ImportSpec>,
+    bindings: ImportBindings,
+}
+
+impl FnMatcher {
+    fn new(fn_names: &[FnName]) -> Self {
+        let mut matcher = Self::default();
+        for fn_name in fn_names {
+            match fn_name {
+                FnName::Path(path) => matcher.paths.push(path.clone()),
+                FnName::Import(spec) => matcher.imports.push(spec.clone()),
+            }
+        }
+        matcher
+    }
+
+    fn resolve(&mut self, module: &Module) {
+        self.bindings = ImportBindings::resolve(module, self.imports.clone());
+    }
+
+    fn matches(&self, callee: &Expr) -> bool {
+        if self.bindings.is_fn(callee) {
+            return true;
+        }
+        let Some(path) = callee_path(callee) else {
+            return false;
+        };
+        self.paths.iter().any(|name| matches_fn_name(name, &path))
+    }
+}
+
 pub struct FnKeyCollector {
-    pub keys: Vec<String>,
-    pub fn_names: Vec<String>,
+    pub keys: Vec<Message>,
+    /// Keys that can't be statically evaluated (parameters, calls, `${}` with unknown values...)
+    pub dynamic: Vec<Span>,
+    fns: FnMatcher,
+    context_fns: FnMatcher,
+    descriptor_fns: FnMatcher,
+    bundle_fns: FnMatcher,
+    descriptors: Option<Descriptors>,
+    components: Vec<Component>,
+    default_messages: bool,
+    comments: SingleThreadedComments,
+    comment_prefix: String,
+    /// Start of the enclosing statements/properties, whose leading comments also apply
+    owners: Vec<BytePos>,
+    consts: ConstMap,
 }
 
-impl Visit for FnKeyCollector {
-    fn visit_call_expr(&mut self, expr: &swc_ecma_ast::CallExpr) {
-        use swc_ecma_ast::{Expr, ExprOrSpread};
-
-        if let Callee::Expr(boxed_expr) = &expr.callee {
-            if let Expr::Ident(ident) = &**boxed_expr {
-                if self.fn_names.contains(&ident.sym.to_string()) {
-                    if let Some(ExprOrSpread { expr, .. }) = expr.args.first() {
-                        if let Expr::Lit(swc_ecma_ast::Lit::Str(s)) = &**expr {
-                            self.keys.push(s.value.to_string());
-                        }
-                    }
+impl FnKeyCollector {
+    pub fn new(target: &Target) -> Self {
+        let descriptors = target.descriptors.clone();
+        let (descriptor_fns, bundle_fns) = match &descriptors {
+            Some(d) => (
+                FnMatcher::new(&d.fn_names),
+                FnMatcher::new(&d.bundle_fn_names),
+            ),
+            None => Default::default(),
+        };
+        Self {
+            keys: vec![],
+            dynamic: vec![],
+            fns: FnMatcher::new(&target.fn_names),
+            context_fns: FnMatcher::new(&target.context_fn_names),
+            descriptor_fns,
+            bundle_fns,
+            descriptors,
+            components: target.components.clone(),
+            default_messages: target.default_messages,
+            comments: SingleThreadedComments::default(),
+            comment_prefix: target.comment_prefix.clone(),
+            owners: vec![],
+            consts: ConstMap::new(),
+        }
+    }
+
+    /// Run `collect` and attach the translator comment preceding `lo` to the new keys.
+    fn with_comment(&mut self, lo: BytePos, collect: impl FnOnce(&mut Self)) {
+        let start = self.keys.len();
+        collect(self);
+        if self.keys.len() == start {
+            return;
+        }
+        if let Some(comment) = self.translator_comment(lo) {
+            for message in &mut self.keys[start..] {
+                message.comment = Some(comment.clone());
+            }
+        }
+    }
+
+    fn translator_comment(&self, lo: BytePos) -> Option<String> {
+        let owner = self.owners.last().copied().filter(|owner| *owner != lo);
+        let positions = std::iter::once(lo).chain(owner);
+        let lines: Vec<String> = positions
+            .filter_map(|pos| self.comments.get_leading(pos))
+            .flatten()
+            .filter_map(|comment| {
+                let text = comment.text.trim().trim_start_matches('*').trim();
+                text.strip_prefix(&self.comment_prefix)
+                    .map(|rest| rest.trim().to_string())
+            })
+            .collect();
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    fn with_owner(&mut self, lo: BytePos, visit: impl FnOnce(&mut Self)) {
+        self.owners.push(lo);
+        visit(self);
+        self.owners.pop();
+    }
+
+    fn collect_call(&mut self, callee: &Expr, args: &[ExprOrSpread]) {
+        if self.fns.matches(callee) {
+            self.collect_args(args, None);
+        } else if self.context_fns.matches(callee) {
+            self.collect_context_args(args);
+        } else if self.descriptor_fns.matches(callee) {
+            self.collect_first_arg(args, Self::collect_descriptor);
+        } else if self.bundle_fns.matches(callee) {
+            self.collect_first_arg(args, Self::collect_bundle);
+        }
+    }
+
+    /// `tc("verb", "Order")`: the first argument is the context.
+    fn collect_context_args(&mut self, args: &[ExprOrSpread]) {
+        let Some(ExprOrSpread { spread, expr }) = args.first() else {
+            return;
+        };
+        match expr_to_key(expr, &self.consts) {
+            Some(context) if spread.is_none() => self.collect_args(&args[1..], Some(context)),
+            _ => self.dynamic.push(expr.span()),
+        }
+    }
+
+    fn collect_args(&mut self, args: &[ExprOrSpread], context: Option<String>) {
+        let Some(ExprOrSpread { spread, expr }) = args.first() else {
+            return;
+        };
+        if spread.is_none()
+            && self.descriptors.is_some()
+            && object_lit(expr, &self.consts).is_some()
+        {
+            self.collect_descriptor(expr);
+            return;
+        }
+        let keys = match expr_to_keys(expr, &self.consts) {
+            Some(keys) if spread.is_none() => keys,
+            _ => {
+                self.dynamic.push(expr.span());
+                return;
+            }
+        };
+        let options = args
+            .get(1)
+            .filter(|arg| arg.spread.is_none())
+            .map(|arg| &*arg.expr);
+        let default = match options {
+            Some(options) if self.default_messages => self.default_message(options),
+            _ => None,
+        };
+        let context = match options.and_then(|o| object_prop(o, "context", &self.consts)) {
+            Some(expr) if context.is_none() => match expr_to_key(expr, &self.consts) {
+                Some(context) => Some(context),
+                None => {
+                    self.dynamic.push(expr.span());
+                    return;
                 }
+            },
+            _ => context,
+        };
+        for key in keys {
+            self.keys.push(Message {
+                key,
+                default: default.clone(),
+                description: None,
+                comment: None,
+                context: context.clone(),
+                span: expr.span(),
+                location: Location::default(),
+            });
+        }
+    }
+
+    fn collect_first_arg(&mut self, args: &[ExprOrSpread], collect: fn(&mut Self, &Expr)) {
+        match args.first() {
+            Some(ExprOrSpread { spread: None, expr }) => collect(self, expr),
+            Some(ExprOrSpread { expr, .. }) => self.dynamic.push(expr.span()),
+            None => {}
+        }
+    }
+
+    /// Second argument of `t("id", "Default text")` or `t("id", { defaultMessage: "..." })`.
+    fn default_message(&self, expr: &Expr) -> Option<String> {
+        match object_prop(expr, "defaultMessage", &self.consts) {
+            Some(default) => expr_to_key(default, &self.consts),
+            None => expr_to_key(expr, &self.consts),
+        }
+    }
+
+    /// `{ id: "x", defaultMessage: "...", description: "..." }`, keyed by the default
+    /// message when there is no id.
+    fn collect_descriptor(&mut self, expr: &Expr) {
+        let Some(descriptors) = &self.descriptors else {
+            return;
+        };
+        let prop = |name: &str| object_prop(expr, name, &self.consts);
+        let default = prop(&descriptors.default_message).map(|e| expr_to_key(e, &self.consts));
+        let description = prop(&descriptors.description).and_then(|e| expr_to_key(e, &self.consts));
+        let key = match (prop(&descriptors.id), &default) {
+            (Some(id), _) => expr_to_key(id, &self.consts),
+            (None, Some(default)) => default.clone(),
+            (None, None) => None,
+        };
+        match key {
+            Some(key) => self.keys.push(Message {
+                key,
+                default: default.flatten(),
+                description,
+                comment: None,
+                context: None,
+                span: expr.span(),
+                location: Location::default(),
+            }),
+            None => self.dynamic.push(expr.span()),
+        }
+    }
+
+    /// `defineMessages({ save: { id: "x", ... }, ... })`
+    fn collect_bundle(&mut self, expr: &Expr) {
+        let Some(obj) = object_lit(expr, &self.consts) else {
+            self.dynamic.push(expr.span());
+            return;
+        };
+        let descriptors: Vec<Expr> = obj
+            .props
+            .iter()
+            .filter_map(|prop| match prop {
+                PropOrSpread::Prop(prop) => match &**prop {
+                    Prop::KeyValue(kv) => Some((*kv.value).clone()),
+                    _ => None,
+                },
+                PropOrSpread::Spread(_) => None,
+            })
+            .collect();
+        for descriptor in &descriptors {
+            self.collect_descriptor(descriptor);
+        }
+    }
+
+    /// `<Trans i18nKey="key">Default</Trans>` or `<T>key</T>`
+    fn collect_jsx(&mut self, el: &JSXElement) {
+        let name = jsx_element_name(&el.opening.name);
+        let Some(component) = self.components.iter().find(|c| c.name == name) else {
+            return;
+        };
+        let Some(attribute) = &component.attribute else {
+            match self.jsx_children_text(&el.children) {
+                Ok(Some(key)) => self.keys.push(Message {
+                    key,
+                    default: None,
+                    description: None,
+                    comment: None,
+                    context: None,
+                    span: el.span,
+                    location: Location::default(),
+                }),
+                Ok(None) => {}
+                Err(span) => self.dynamic.push(span),
+            }
+            return;
+        };
+
+        let value = el.opening.attrs.iter().find_map(|attr| match attr {
+            JSXAttrOrSpread::JSXAttr(JSXAttr {
+                name: JSXAttrName::Ident(ident),
+                value,
+                ..
+            }) if ident.sym == **attribute => value.as_ref(),
+            _ => None,
+        });
+        let keys = match value {
+            Some(JSXAttrValue::Lit(Lit::Str(s))) => Some(vec![s.value.to_string()]),
+            Some(JSXAttrValue::JSXExprContainer(JSXExprContainer {
+                expr: JSXExpr::Expr(expr),
+                ..
+            })) => expr_to_keys(expr, &self.consts),
+            Some(_) => None,
+            // Without its key attribute the component isn't a translation
+            None => return,
+        };
+        let Some(keys) = keys else {
+            self.dynamic.push(value.map_or(el.span, |v| v.span()));
+            return;
+        };
+        let default = match self.default_messages {
+            true => self.jsx_children_text(&el.children).ok().flatten(),
+            false => None,
+        };
+        for key in keys {
+            self.keys.push(Message {
+                key,
+                default: default.clone(),
+                description: None,
+                comment: None,
+                context: None,
+                span: el.opening.span,
+                location: Location::default(),
+            });
+        }
+    }
+
+    /// Static text of JSX children (`text` and `{"literal"}`), `Err` with the span of the
+    /// first child that isn't static.
+    fn jsx_children_text(&self, children: &[JSXElementChild]) -> Result<Option<String>, Span> {
+        let mut text = String::new();
+        for child in children {
+            match child {
+                JSXElementChild::JSXText(t) => text.push_str(&clean_jsx_text(&t.value)),
+                JSXElementChild::JSXExprContainer(JSXExprContainer {
+                    expr: JSXExpr::JSXEmptyExpr(_),
+                    ..
+                }) => {}
+                JSXElementChild::JSXExprContainer(JSXExprContainer {
+                    expr: JSXExpr::Expr(expr),
+                    ..
+                }) => match expr_to_key(expr, &self.consts) {
+                    Some(value) => text.push_str(&value),
+                    None => return Err(expr.span()),
+                },
+                child => return Err(child.span()),
+            }
+        }
+        Ok((!text.is_empty()).then_some(text))
+    }
+
+    fn collect_tpl(&mut self, tpl: &Tpl) {
+        match tpl_to_keys(tpl, &self.consts) {
+            Some(keys) => self.keys.extend(keys.into_iter().map(|key| Message {
+                key,
+                default: None,
+                description: None,
+                comment: None,
+                context: None,
+                span: tpl.span,
+                location: Location::default(),
+            })),
+            None => self.dynamic.push(tpl.span),
+        }
+    }
+}
+
+impl Visit for FnKeyCollector {
+    fn visit_module(&mut self, module: &Module) {
+        self.consts = module_consts(module);
+        self.fns.resolve(module);
+        self.context_fns.resolve(module);
+        self.descriptor_fns.resolve(module);
+        self.bundle_fns.resolve(module);
+        // `const messages = defineMessages({...})` evaluates to its argument
+        for init in self.consts.values_mut() {
+            if let Expr::Call(call) = init
+                && let Callee::Expr(callee) = &call.callee
+                && self.bundle_fns.matches(callee)
+                && let Some(ExprOrSpread { spread: None, expr }) = call.args.first()
+            {
+                *init = (**expr).clone();
             }
         }
+        module.visit_children_with(self);
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        self.with_owner(stmt.span_lo(), |this| stmt.visit_children_with(this));
+    }
+
+    fn visit_module_decl(&mut self, decl: &ModuleDecl) {
+        self.with_owner(decl.span_lo(), |this| decl.visit_children_with(this));
+    }
+
+    fn visit_prop(&mut self, prop: &Prop) {
+        self.with_owner(prop.span_lo(), |this| prop.visit_children_with(this));
+    }
+
+    fn visit_class_member(&mut self, member: &ClassMember) {
+        self.with_owner(member.span_lo(), |this| member.visit_children_with(this));
+    }
+
+    fn visit_call_expr(&mut self, expr: &CallExpr) {
+        if let Callee::Expr(callee) = &expr.callee {
+            self.with_comment(expr.span.lo, |this| this.collect_call(callee, &expr.args));
+        }
         expr.visit_children_with(self);
     }
+
+    fn visit_opt_call(&mut self, call: &OptCall) {
+        self.with_comment(call.span.lo, |this| {
+            this.collect_call(&call.callee, &call.args)
+        });
+        call.visit_children_with(self);
+    }
+
+    fn visit_jsx_element(&mut self, el: &JSXElement) {
+        if !self.components.is_empty() {
+            self.with_comment(el.span.lo, |this| this.collect_jsx(el));
+        }
+        el.visit_children_with(self);
+    }
+
+    fn visit_tagged_tpl(&mut self, tagged: &TaggedTpl) {
+        if self.fns.matches(&tagged.tag) {
+            self.with_comment(tagged.span.lo, |this| this.collect_tpl(&tagged.tpl));
+        }
+        tagged.visit_children_with(self);
+    }
+}
+
+/// Top-level `const` declarations (exported or not) with a plain identifier binding.
+fn module_consts(module: &Module) -> ConstMap {
+    let mut consts = ConstMap::new();
+    for item in &module.body {
+        let var = match item {
+            ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) => var,
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Var(var),
+                ..
+            })) => var,
+            _ => continue,
+        };
+        if var.kind != VarDeclKind::Const {
+            continue;
+        }
+        for decl in &var.decls {
+            if let Pat::Ident(ident) = &decl.name
+                && let Some(init) = &decl.init
+            {
+                consts.insert(ident.id.to_id(), (**init).clone());
+            }
+        }
+    }
+    consts
+}
+
+/// Flatten a callee like `t`, `this.t`, `ctx.intl.t` or `i18n?.t` into its path segments.
+fn callee_path(expr: &Expr) -> Option<Vec<String>> {
+    match expr {
+        Expr::Ident(ident) => Some(vec![ident.sym.to_string()]),
+        Expr::This(_) => Some(vec!["this".to_string()]),
+        Expr::Member(member) => member_path(member),
+        Expr::OptChain(chain) => match &*chain.base {
+            OptChainBase::Member(member) => member_path(member),
+            OptChainBase::Call(_) => None,
+        },
+        Expr::Paren(paren) => callee_path(&paren.expr),
+        Expr::TsNonNull(e) => callee_path(&e.expr),
+        _ => None,
+    }
+}
+
+fn member_path(member: &MemberExpr) -> Option<Vec<String>> {
+    let mut path = callee_path(&member.obj)?;
+    match &member.prop {
+        MemberProp::Ident(ident) => path.push(ident.sym.to_string()),
+        MemberProp::Computed(computed) => match &*computed.expr {
+            Expr::Lit(Lit::Str(s)) => path.push(s.value.to_string()),
+            _ => return None,
+        },
+        MemberProp::PrivateName(_) => return None,
+    }
+    Some(path)
+}
+
+/// Match a configured name such as `t`, `i18n.t` or `*.t` against a callee path.
+/// `*` matches exactly one segment, `**` matches any number of segments.
+fn matches_fn_name(pattern: &str, path: &[String]) -> bool {
+    let pattern: Vec<&str> = pattern.split('.').collect();
+    match_segments(&pattern, path)
+}
+
+fn match_segments(pattern: &[&str], path: &[String]) -> bool {
+    match (pattern.split_first(), path.split_first()) {
+        (None, None) => true,
+        (Some((&"**", rest)), _) => {
+            match_segments(rest, path) || (!path.is_empty() && match_segments(pattern, &path[1..]))
+        }
+        (Some((segment, rest)), Some((name, tail))) => {
+            (*segment == "*" || segment == name) && match_segments(rest, tail)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, path: &str) -> bool {
+        let path: Vec<String> = path.split('.').map(String::from).collect();
+        matches_fn_name(pattern, &path)
+    }
+
+    #[test]
+    fn literal_segments() {
+        assert!(matches("t", "t"));
+        assert!(matches("i18n.t", "i18n.t"));
+        assert!(!matches("t", "i18n.t"));
+        assert!(!matches("i18n.t", "t"));
+    }
+
+    #[test]
+    fn star_matches_one_segment() {
+        assert!(matches("*.t", "props.t"));
+        assert!(matches("*.t", "this.t"));
+        assert!(!matches("*.t", "t"));
+        assert!(!matches("*.t", "a.b.t"));
+    }
+
+    #[test]
+    fn double_star_matches_any_segments() {
+        assert!(matches("ctx.**.t", "ctx.t"));
+        assert!(matches("ctx.**.t", "ctx.intl.t"));
+        assert!(matches("ctx.**.t", "ctx.a.b.t"));
+        assert!(matches("**.t", "t"));
+        assert!(!matches("ctx.**.t", "other.intl.t"));
+        assert!(!matches("ctx.**.t", "ctx.intl.tr"));
+    }
+
+    fn target() -> Target {
+        serde_json::from_value(serde_json::json!({ "includes": [], "excludes": [] })).unwrap()
+    }
+
+    #[test]
+    fn shadowed_constants_are_dynamic() {
+        let source = r#"
+const KEY = "Global";
+function f(KEY) { t(KEY); }
+function g() { const KEY = "Local"; t(KEY); }
+t(KEY);
+"#;
+        let file = collect_file(
+            Path::new("a.ts"),
+            source.to_string(),
+            &target(),
+            &Extractors::default(),
+        )
+        .unwrap();
+        let keys: Vec<&str> = file.keys.iter().map(|m| m.key.as_str()).collect();
+        assert_eq!(keys, ["Global"]);
+        assert_eq!(
+            file.dynamic,
+            [Location { line: 3, col: 21 }, Location { line: 4, col: 39 }]
+        );
+    }
+
+    fn import_target() -> Target {
+        serde_json::from_value(serde_json::json!({
+            "includes": [],
+            "excludes": [],
+            "fnNames": [
+                { "module": "@nuclenergy/t", "name": "t" },
+                { "module": "react-i18next", "name": "useTranslation", "member": "t" },
+            ],
+        }))
+        .unwrap()
+    }
+
+    fn import_keys(source: &str) -> Vec<String> {
+        let file = collect_file(
+            Path::new("a.ts"),
+            source.to_string(),
+            &import_target(),
+            &Extractors::default(),
+        )
+        .unwrap();
+        file.keys.into_iter().map(|m| m.key).collect()
+    }
+
+    #[test]
+    fn shadowing_bindings_dont_match_imports() {
+        let source = r#"
+import { t } from "@nuclenergy/t";
+t("Imported");
+function f(t) { t("Parameter"); }
+for (const t of fns) { t("Loop"); }
+const g = (t) => t("Arrow");
+"#;
+        assert_eq!(import_keys(source), ["Imported"]);
+    }
+
+    #[test]
+    fn renamed_imports() {
+        let source = r#"
+import { t as tr } from "@nuclenergy/t";
+import { t } from "other";
+tr("Renamed");
+const alias = tr;
+alias("Alias");
+t("Other module");
+"#;
+        assert_eq!(import_keys(source), ["Renamed", "Alias"]);
+    }
+
+    #[test]
+    fn destructured_members() {
+        let source = r#"
+import { useTranslation } from "react-i18next";
+const { t: tt } = useTranslation();
+tt("Destructured");
+useTranslation().t("Member");
+const { t } = useOther();
+t("Other factory");
+"#;
+        assert_eq!(import_keys(source), ["Destructured", "Member"]);
+    }
+
+    #[test]
+    fn namespace_imports() {
+        let source = r#"
+import * as i18n from "@nuclenergy/t";
+import * as other from "other";
+i18n.t("Namespace");
+other.t("Other module");
+"#;
+        assert_eq!(import_keys(source), ["Namespace"]);
+    }
+
+    #[test]
+    fn component_directives_come_from_comments() {
+        let source = r#"<!-- t-cli-disable -->
+<template>
+  <p>{{ t("Disabled") }}</p>
+  <!-- t-cli-enable -->
+  <!-- t-cli-ignore-next-line -->
+  <p>{{ t("Ignored") }}</p>
+  <p :title="t('Title')">{{ t("Body") }}</p>
+</template>
+<script setup>
+const hint = "Add // t-cli-ignore-file to skip a file";
+// t-cli-ignore-next-line
+t("Script ignored");
+t("Script");
+</script>
+"#;
+        let file = collect_file(
+            Path::new("a.vue"),
+            source.to_string(),
+            &target(),
+            &Extractors::default(),
+        )
+        .unwrap();
+        let keys: Vec<&str> = file.keys.iter().map(|m| m.key.as_str()).collect();
+        assert_eq!(keys, ["Title", "Body", "Script"]);
+    }
+
+    #[tokio::test]
+    async fn inherited_keys_are_not_repeated() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        std::fs::create_dir_all(root.join("_t")).unwrap();
+        std::fs::write(root.join("_t/en.json"), r#"{ "Save": "Save" }"#).unwrap();
+
+        let mut config = Config::default();
+        config.targets[0].inherit = true;
+        let workspace = |dir: PathBuf| Workspace {
+            dir: dir.clone(),
+            dirs: vec![dir],
+            output_sources: vec![],
+        };
+        let file = |keys: &[&str]| FileKeys {
+            keys: keys
+                .iter()
+                .map(|key| Message::new(key.to_string(), None, Span::default()))
+                .collect(),
+            dynamic: vec![],
+        };
+        let scan = TargetScan {
+            target: config.targets[0].clone(),
+            workspaces: vec![workspace(root.to_path_buf()), workspace(root.join("page"))],
+            files: vec![
+                vec![(root.join("a.ts"), file(&["Cancel"]))],
+                vec![(root.join("page/b.ts"), file(&["Cancel", "Save", "Title"]))],
+            ],
+            unknown: HashSet::new(),
+        };
+        let mut dictionaries = Dictionaries::default();
+        merge_collected(&config, &[scan], &mut dictionaries, false)
+            .await
+            .unwrap();
+
+        let keys = dictionaries.keys(&root.join("_t/en.json")).await.unwrap();
+        assert_eq!(keys, ["Cancel", "Save"]);
+        let keys = dictionaries
+            .keys(&root.join("page/_t/en.json"))
+            .await
+            .unwrap();
+        assert_eq!(keys, ["Title"]);
+    }
 }
@@ -5,13 +5,19 @@
 use swc_ecma_parser::{Lexer, Parser, StringInput, Syntax, TsSyntax};
 use tokio::fs::read_to_string;
 
-use crate::{error::Error, utils::expr_to_value::expr_to_value};
+use crate::{
+    error::Error,
+    utils::{diagnostic::render_parse_error, expr_to_value::expr_to_value},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
     pub languages: LanguageNode,
     pub targets: Vec<Target>,
+    /// Skip files ignored by `.gitignore` (and `.git/info/exclude`) in every target
+    #[serde(default)]
+    pub respect_gitignore: bool,
 }
 
 impl Default for Config {
@@ -26,11 +32,34 @@ fn default() -> Self {
                 excludes: vec!["node_modules".to_string(), ".*".to_string()],
                 output: default_output(),
                 fn_names: default_fn_names(),
+                context_fn_names: vec![],
+                context_separator: default_context_separator(),
+                default_messages: false,
+                descriptors: None,
+                components: vec![],
+                comment_prefix: default_comment_prefix(),
+                syntax: SyntaxOptions::default(),
+                extractors: vec![],
+                content_files: vec![],
+                file_includes: vec![],
+                file_excludes: vec![],
+                scope: Scope::default(),
+                scope_depth: 0,
+                inherit: false,
+                respect_gitignore: None,
             }],
+            respect_gitignore: false,
         }
     }
 }
 
+impl Config {
+    /// Output directory names of all targets, skipped when discovering workspaces.
+    pub fn outputs(&self) -> Vec<String> {
+        self.targets.iter().map(|t| t.output.clone()).collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LanguageNode {
@@ -62,15 +91,173 @@ pub struct Target {
     #[serde(default = "default_output")]
     pub output: String,
     #[serde(default = "default_fn_names")]
-    pub fn_names: Vec<String>,
+    pub fn_names: Vec<FnName>,
+    /// Functions taking a context before the key, e.g. `tc("verb", "Order")`
+    #[serde(default)]
+    pub context_fn_names: Vec<FnName>,
+    /// Joins context and key into one dictionary key
+    #[serde(default = "default_context_separator")]
+    pub context_separator: String,
+    /// Treat the first argument as a stable ID and the second one (or its `defaultMessage`)
+    /// as the default-language text
+    #[serde(default)]
+    pub default_messages: bool,
+    /// Message descriptor extraction, e.g. `formatMessage({ id, defaultMessage })`
+    #[serde(default)]
+    pub descriptors: Option<Descriptors>,
+    /// JSX components carrying keys, e.g. `<Trans i18nKey="...">` or `<T>text</T>`
+    #[serde(default)]
+    pub components: Vec<Component>,
+    /// Prefix marking translator comments before a call, e.g. `// t: verb`
+    #[serde(default = "default_comment_prefix")]
+    pub comment_prefix: String,
+    /// Parser options overriding the defaults picked from each file's extension
+    #[serde(default)]
+    pub syntax: SyntaxOptions,
+    /// Regex extractors for non-JavaScript files, e.g. Markdown or backend templates
+    #[serde(default)]
+    pub extractors: Vec<Extractor>,
+    /// JSON/YAML files whose selected string values are keys
+    #[serde(default)]
+    pub content_files: Vec<ContentFile>,
+    /// Globs a source file must match to be scanned, e.g. `**/*.tsx` (all files when empty)
+    #[serde(default)]
+    pub file_includes: Vec<String>,
+    /// Globs of source files to skip, e.g. `**/*.test.tsx`
+    #[serde(default)]
+    pub file_excludes: Vec<String>,
+    /// Which directories share a dictionary
+    #[serde(default)]
+    pub scope: Scope,
+    /// With `subtree` scope, directories up to this many levels below an include get their
+    /// own dictionary
+    #[serde(default)]
+    pub scope_depth: usize,
+    /// Merge the nearest ancestor workspace's dictionary into each generated `index.ts` and
+    /// don't repeat keys it already defines
+    #[serde(default)]
+    pub inherit: bool,
+    /// Overrides the top-level `respectGitignore` for this target
+    #[serde(default)]
+    pub respect_gitignore: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Scope {
+    /// Every directory has its own dictionary of its immediate files
+    #[default]
+    Directory,
+    /// Nested directories contribute to the nearest ancestor owning a dictionary
+    Subtree,
+    /// One dictionary per include
+    Target,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Extractor {
+    /// Globs matched against file paths relative to the project root, e.g. `**/*.md`
+    pub files: Vec<String>,
+    /// Regexes with a `key` capture group (and optionally `context`)
+    pub patterns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentFile {
+    /// Globs matched against file paths relative to the project root, e.g. `content/*.json`
+    pub files: Vec<String>,
+    /// JSONPath selectors of the translated fields, e.g. `$.sections[*].title`
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyntaxOptions {
+    #[serde(default)]
+    pub typescript: Option<bool>,
+    #[serde(default)]
+    pub jsx: Option<bool>,
+    #[serde(default)]
+    pub decorators: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Component {
+    /// Element name, e.g. `T` or `Intl.Trans`
+    pub name: String,
+    /// Attribute holding the key; the element's text children are the key when unset
+    #[serde(default)]
+    pub attribute: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Descriptors {
+    /// Functions taking a single descriptor, e.g. `formatMessage`
+    #[serde(default)]
+    pub fn_names: Vec<FnName>,
+    /// Functions taking an object of descriptors, e.g. `defineMessages`
+    #[serde(default)]
+    pub bundle_fn_names: Vec<FnName>,
+    #[serde(default = "default_id_prop")]
+    pub id: String,
+    #[serde(default = "default_default_message_prop")]
+    pub default_message: String,
+    #[serde(default = "default_description_prop")]
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FnName {
+    /// Callee path matched by name, e.g. `t`, `i18n.t`, `*.t`
+    Path(String),
+    /// Export of a module, matched only through the local bindings it is imported as
+    Import(ImportSpec),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSpec {
+    /// Module specifier, e.g. `@nuclenergy/t`
+    pub module: String,
+    /// Export name, `default` for the default export
+    pub name: String,
+    /// When set, the export is a factory (e.g. `useTranslation`) whose result holds the
+    /// translation function under this property
+    #[serde(default)]
+    pub member: Option<String>,
 }
 
 fn default_output() -> String {
     "_t".to_string()
 }
 
-fn default_fn_names() -> Vec<String> {
-    vec!["t".to_string()]
+fn default_fn_names() -> Vec<FnName> {
+    vec![FnName::Path("t".to_string())]
+}
+
+fn default_context_separator() -> String {
+    "\u{4}".to_string()
+}
+
+fn default_comment_prefix() -> String {
+    "t:".to_string()
+}
+
+fn default_id_prop() -> String {
+    "id".to_string()
+}
+
+fn default_default_message_prop() -> String {
+    "defaultMessage".to_string()
+}
+
+fn default_description_prop() -> String {
+    "description".to_string()
 }
 
 pub async fn load_config_from_file(path: &str) -> Result<Config, Error> {
@@ -90,7 +277,9 @@ pub async fn load_config_from_file(path: &str) -> Result<Config, Error> {
     );
 
     let mut parser = Parser::new_from(lexer);
-    let module = parser.parse_module().map_err(Error::ParseModule)?;
+    let module = parser
+        .parse_module()
+        .map_err(|e| Error::ParseModule(render_parse_error(&cm, e)))?;
 
     // 1. Collect all variable definitions
     let mut var_map = std::collections::HashMap::new();
@@ -100,10 +289,10 @@ pub async fn load_config_from_file(path: &str) -> Result<Config, Error> {
         ))) = item
         {
             for decl in &var.decls {
-                if let Some(init) = &decl.init {
-                    if let swc_ecma_ast::Pat::Ident(ident) = &decl.name {
-                        var_map.insert(ident.id.sym.to_string(), init.as_ref());
-                    }
+                if let Some(init) = &decl.init
+                    && let swc_ecma_ast::Pat::Ident(ident) = &decl.name
+                {
+                    var_map.insert(ident.id.sym.to_string(), init.as_ref());
                 }
             }
         }
@@ -126,7 +315,13 @@ pub async fn load_config_from_file(path: &str) -> Result<Config, Error> {
             };
 
             let value: Value = expr_to_value(target_expr)?;
-            return Ok(serde_json::from_value(value)?);
+            let mut config: Config = serde_json::from_value(value)?;
+            for target in &mut config.targets {
+                target
+                    .respect_gitignore
+                    .get_or_insert(config.respect_gitignore);
+            }
+            return Ok(config);
         }
     }
 
@@ -0,0 +1,102 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use indexmap::IndexMap;
+use tokio::fs::{create_dir_all, read_to_string, try_exists, write};
+
+use crate::{collect::MessageMeta, error::Error};
+
+/// Keys of a `{output}/{lang}.json` file in file order, `None` for untranslated entries.
+pub type Dictionary = IndexMap<String, Option<String>>;
+
+/// State of a `{output}/{lang}.json` file.
+pub enum DictionaryFile {
+    Missing,
+    Invalid(serde_json::Error),
+    Valid(Dictionary),
+}
+
+/// `{output}/{lang}.json` and `meta.json` files, each read once on first use and written back
+/// once by [`Dictionaries::save`] when changed.
+#[derive(Default)]
+pub struct Dictionaries {
+    dictionaries: HashMap<PathBuf, DictionaryFile>,
+    /// `None` when the file isn't valid JSON
+    metas: HashMap<PathBuf, Option<IndexMap<String, MessageMeta>>>,
+    changed: HashSet<PathBuf>,
+}
+
+impl Dictionaries {
+    pub async fn dictionary(&mut self, path: &Path) -> Result<&DictionaryFile, Error> {
+        if !self.dictionaries.contains_key(path) {
+            let file = if try_exists(path).await? {
+                match serde_json::from_str(&read_to_string(path).await?) {
+                    Ok(dictionary) => DictionaryFile::Valid(dictionary),
+                    Err(e) => DictionaryFile::Invalid(e),
+                }
+            } else {
+                DictionaryFile::Missing
+            };
+            self.dictionaries.insert(path.to_path_buf(), file);
+        }
+        Ok(&self.dictionaries[path])
+    }
+
+    /// Keys of a dictionary, empty when missing or invalid.
+    pub async fn keys(&mut self, path: &Path) -> Result<Vec<String>, Error> {
+        Ok(match self.dictionary(path).await? {
+            DictionaryFile::Valid(dictionary) => dictionary.keys().cloned().collect(),
+            _ => vec![],
+        })
+    }
+
+    pub fn set_dictionary(&mut self, path: &Path, dictionary: Dictionary) {
+        self.dictionaries
+            .insert(path.to_path_buf(), DictionaryFile::Valid(dictionary));
+        self.changed.insert(path.to_path_buf());
+    }
+
+    /// Entries of a `meta.json`, empty when missing and `None` when invalid, so callers skip
+    /// it instead of overwriting it.
+    pub async fn meta(
+        &mut self,
+        path: &Path,
+    ) -> Result<Option<&IndexMap<String, MessageMeta>>, Error> {
+        if !self.metas.contains_key(path) {
+            let meta = if try_exists(path).await? {
+                serde_json::from_str(&read_to_string(path).await?).ok()
+            } else {
+                Some(IndexMap::new())
+            };
+            self.metas.insert(path.to_path_buf(), meta);
+        }
+        Ok(self.metas[path].as_ref())
+    }
+
+    pub fn set_meta(&mut self, path: &Path, meta: IndexMap<String, MessageMeta>) {
+        self.metas.insert(path.to_path_buf(), Some(meta));
+        self.changed.insert(path.to_path_buf());
+    }
+
+    /// Write the files changed since they were read.
+    pub async fn save(&mut self) -> Result<(), Error> {
+        let mut changed: Vec<PathBuf> = self.changed.drain().collect();
+        changed.sort();
+        for path in changed {
+            let json = match (self.dictionaries.get(&path), self.metas.get(&path)) {
+                (Some(DictionaryFile::Valid(dictionary)), _) => {
+                    serde_json::to_string_pretty(dictionary)?
+                }
+                (_, Some(Some(meta))) => serde_json::to_string_pretty(meta)?,
+                _ => continue,
+            };
+            if let Some(parent) = path.parent() {
+                create_dir_all(parent).await?;
+            }
+            write(&path, json).await?;
+        }
+        Ok(())
+    }
+}
@@ -1,61 +1,121 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, path::Path};
 
-use tokio::fs::{create_dir_all, read_to_string, try_exists, write};
+use tokio::fs::{create_dir_all, try_exists, write};
 
 use crate::{
-    config::{Config, LanguageNode},
+    collect::GENERATED_FILE,
+    config::{Config, LanguageNode, Target},
+    dictionaries::{Dictionaries, DictionaryFile},
     error::Error,
-    utils::resolve::resolve_workspaces,
+    utils::resolve::{Workspace, ancestors, resolve_workspaces},
 };
 
 pub async fn run_tgen(config: Config, verbose: bool) -> Result<(), Error> {
-    for target in config.targets {
-        let workspaces = resolve_workspaces(".", &target.includes, &target.excludes)?;
-        for workspace in workspaces {
-            let output_dir = workspace.join(&target.output);
-            if !try_exists(&output_dir).await? {
-                continue;
-            }
+    let outputs = config.outputs();
+    let mut dictionaries = Dictionaries::default();
+    for target in &config.targets {
+        let workspaces = resolve_workspaces(".", target, &outputs)?;
+        generate_target(&config, target, &workspaces, &mut dictionaries, verbose).await?;
+    }
+    Ok(())
+}
 
-            let mut all_translations: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+/// Write `{output}/index.ts` of every workspace that has an output directory, from the
+/// dictionaries as loaded or changed in `dictionaries`.
+pub async fn generate_target(
+    config: &Config,
+    target: &Target,
+    workspaces: &[Workspace],
+    dictionaries: &mut Dictionaries,
+    verbose: bool,
+) -> Result<(), Error> {
+    for workspace in workspaces {
+        let output_dir = workspace.dir.join(&target.output);
+        if !try_exists(&output_dir).await? {
+            continue;
+        }
 
-            let mut lang_order = Vec::new();
-            walk_language_tree(&config.languages, None, &mut lang_order);
+        let mut all_translations: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
 
-            for (lang, parent_lang) in lang_order {
-                let mut lang_data = match parent_lang {
-                    Some(parent) => all_translations.get(&parent).cloned().unwrap_or_default(),
-                    None => BTreeMap::new(),
-                };
+        let mut lang_order = Vec::new();
+        walk_language_tree(&config.languages, None, &mut lang_order);
 
-                let file_path = output_dir.join(format!("{lang}.json"));
-                if try_exists(&file_path).await? {
-                    let content = read_to_string(&file_path).await?;
-                    let file_map: BTreeMap<String, Option<String>> =
-                        serde_json::from_str(&content)?;
+        for (lang, parent_lang) in lang_order {
+            let mut lang_data = match parent_lang {
+                Some(parent) => all_translations.get(&parent).cloned().unwrap_or_default(),
+                None => BTreeMap::new(),
+            };
 
+            let file_path = output_dir.join(format!("{lang}.json"));
+            match dictionaries.dictionary(&file_path).await? {
+                DictionaryFile::Valid(file_map) => {
                     for (k, v) in file_map {
                         if let Some(real_value) = v {
-                            lang_data.insert(k, real_value);
+                            lang_data.insert(k.clone(), real_value.clone());
                         }
                     }
                 }
-                all_translations.insert(lang.clone(), lang_data);
+                DictionaryFile::Invalid(e) => {
+                    return Err(Error::Error(format!("{}: {e}", file_path.display())));
+                }
+                DictionaryFile::Missing => {}
             }
+            all_translations.insert(lang.clone(), lang_data);
+        }
 
-            let output_path = output_dir.join("index.ts");
-            if let Some(parent) = output_path.parent() {
-                create_dir_all(parent).await?;
+        let output_path = output_dir.join(GENERATED_FILE);
+        if let Some(parent) = output_path.parent() {
+            create_dir_all(parent).await?;
+        }
+        // Nearest ancestor dictionary this one is merged over
+        let mut inherited = None;
+        if target.inherit {
+            for ancestor in ancestors(workspaces, &workspace.dir) {
+                if try_exists(ancestor.dir.join(&target.output)).await? {
+                    inherited = Some(ancestor);
+                    break;
+                }
             }
-            let ts_output = format!(
-                "export const {output} = {dictionaries} as const;\n\nexport type Dict = (typeof {output})[keyof typeof {output}];\n",
-                output = target.output,
-                dictionaries = serde_json::to_string_pretty(&all_translations)?
-            );
-            write(&output_path, ts_output).await?;
-            if verbose {
-                println!("Generated: {}", output_path.display());
+        }
+        let dictionaries = match inherited {
+            Some(ancestor) => {
+                let depth = workspace
+                    .dir
+                    .strip_prefix(&ancestor.dir)
+                    .map_or(0, |rel| rel.components().count())
+                    + Path::new(&target.output).components().count();
+                let import_path = format!("{}{}", "../".repeat(depth), target.output);
+                let merged = all_translations
+                    .keys()
+                    .map(|lang| {
+                        let lang = serde_json::to_string(lang)?;
+                        Ok(format!(
+                            "  {lang}: {{ ...inherited[{lang}], ...own[{lang}] }},"
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                format!(
+                    "import {{ {output} as inherited }} from {import_path};\n\nconst own = {own} as const;\n\nexport const {output} = {{\n{merged}\n}}",
+                    output = target.output,
+                    import_path = serde_json::to_string(&import_path)?,
+                    own = serde_json::to_string_pretty(&all_translations)?,
+                    merged = merged.join("\n"),
+                )
             }
+            None => format!(
+                "export const {output} = {own}",
+                output = target.output,
+                own = serde_json::to_string_pretty(&all_translations)?,
+            ),
+        };
+        let ts_output = format!(
+            "{dictionaries} as const;\n\nexport type Dict = (typeof {output})[keyof typeof {output}];\n\n/** Joins context and key: `dict[context + contextSeparator + key]` */\nexport const contextSeparator = {separator};\n",
+            output = target.output,
+            separator = serde_json::to_string(&target.context_separator)?
+        );
+        write(&output_path, ts_output).await?;
+        if verbose {
+            println!("Generated: {}", output_path.display());
         }
     }
     Ok(())
@@ -1,28 +1,50 @@
+use std::process::ExitCode;
+
 use cli::Cli;
 use error::Error;
 
+mod cache;
 mod clean;
 mod cli;
 mod collect;
 mod config;
+mod dictionaries;
 mod error;
 mod generate;
+mod pipeline;
 mod utils;
 
 pub const CONFIG_TEMPLATE_TS: &str = include_str!("../template/t.config.ts");
 
 #[tokio::main]
-async fn main() -> Result<(), Error> {
+async fn main() -> ExitCode {
     let cli: Cli = clap::Parser::parse();
 
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Error> {
     match cli.command {
         cli::Commands::Init { output, force } => {
             generate::init_config::generate_config_file(&output, force).await?;
             println!("Config file generated successfully");
         }
-        cli::Commands::Collect { config, verbose } => {
+        cli::Commands::Collect {
+            config,
+            verbose,
+            collect,
+        } => {
             let config = config::load_config_from_file(&config).await?;
-            collect::run_collect(config, verbose).await?;
+            let parse_errors =
+                collect::run_collect(config, verbose, collect.deny_dynamic, &collect.scan.into())
+                    .await?;
+            collect::report_parse_errors(&parse_errors)?;
             println!("Collected successfully");
         }
         cli::Commands::Generate { config, verbose } => {
@@ -30,22 +52,48 @@ async fn main() -> Result<(), Error> {
             generate::tgen::run_tgen(config, verbose).await?;
             println!("Generated successfully");
         }
-        cli::Commands::Clean { config, verbose } => {
+        cli::Commands::Clean {
+            config,
+            verbose,
+            scan,
+        } => {
             let config = config::load_config_from_file(&config).await?;
-            clean::run_clean(config, verbose).await?;
+            let parse_errors = clean::run_clean(config, verbose, &scan.into()).await?;
+            collect::report_parse_errors(&parse_errors)?;
             println!("Cleaned successfully");
         }
-        cli::Commands::Cg { config, verbose } => {
+        cli::Commands::Cg {
+            config,
+            verbose,
+            collect,
+        } => {
             let config = config::load_config_from_file(&config).await?;
-            collect::run_collect(config.clone(), verbose).await?;
-            generate::tgen::run_tgen(config, verbose).await?;
+            let parse_errors = pipeline::run_pipeline(
+                config,
+                verbose,
+                collect.deny_dynamic,
+                false,
+                &collect.scan.into(),
+            )
+            .await?;
+            collect::report_parse_errors(&parse_errors)?;
             println!("Collected and generated successfully");
         }
-        cli::Commands::Gc { config, verbose } => {
+        cli::Commands::Gc {
+            config,
+            verbose,
+            collect,
+        } => {
             let config = config::load_config_from_file(&config).await?;
-            collect::run_collect(config.clone(), verbose).await?;
-            generate::tgen::run_tgen(config.clone(), verbose).await?;
-            clean::run_clean(config, verbose).await?;
+            let parse_errors = pipeline::run_pipeline(
+                config,
+                verbose,
+                collect.deny_dynamic,
+                true,
+                &collect.scan.into(),
+            )
+            .await?;
+            collect::report_parse_errors(&parse_errors)?;
             println!("Collected, generated and cleaned successfully");
         }
     }
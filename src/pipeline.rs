@@ -0,0 +1,40 @@
+use crate::{
+    clean::merge_clean,
+    collect::{ScanOptions, merge_collected, scan},
+    config::Config,
+    dictionaries::Dictionaries,
+    error::Error,
+    generate::tgen::generate_target,
+};
+
+/// `collect` + `generate` (+ `clean` with `clean`) in one pass: source files are scanned once,
+/// each dictionary is written at most once, and `index.ts` files are generated from the merged
+/// dictionaries kept in memory. Diagnostics of files skipped with `keep_going` are returned.
+pub async fn run_pipeline(
+    config: Config,
+    verbose: bool,
+    deny_dynamic: bool,
+    clean: bool,
+    options: &ScanOptions,
+) -> Result<Vec<String>, Error> {
+    let scan = scan(&config, verbose, options).await?;
+    let mut dictionaries = Dictionaries::default();
+    merge_collected(&config, &scan.targets, &mut dictionaries, deny_dynamic).await?;
+    if clean {
+        merge_clean(&config, &scan.targets, &mut dictionaries, verbose).await?;
+    }
+    // Creates the output directories `generate` looks for
+    dictionaries.save().await?;
+
+    for target in &scan.targets {
+        generate_target(
+            &config,
+            &target.target,
+            &target.workspaces,
+            &mut dictionaries,
+            verbose,
+        )
+        .await?;
+    }
+    Ok(scan.parse_errors)
+}
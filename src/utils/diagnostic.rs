@@ -0,0 +1,32 @@
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use swc_common::{SourceMap, errors::Handler, sync::Lrc};
+
+/// Render a parse error the way swc reports it: message, `file:line:col` and a source snippet.
+pub fn render_parse_error(cm: &Lrc<SourceMap>, error: swc_ecma_parser::error::Error) -> String {
+    let buffer = Buffer::default();
+    let handler = Handler::with_emitter_writer(Box::new(buffer.clone()), Some(cm.clone()));
+    error.into_diagnostic(&handler).emit();
+
+    let bytes = buffer.0.lock().map(|b| b.clone()).unwrap_or_default();
+    String::from_utf8_lossy(&bytes).trim_end().to_string()
+}
+
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Ok(mut bytes) = self.0.lock() {
+            bytes.extend_from_slice(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
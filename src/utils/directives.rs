@@ -0,0 +1,96 @@
+use std::ops::RangeInclusive;
+
+pub const IGNORE_FILE: &str = "t-cli-ignore-file";
+pub const IGNORE_NEXT_LINE: &str = "t-cli-ignore-next-line";
+pub const DISABLE: &str = "t-cli-disable";
+pub const ENABLE: &str = "t-cli-enable";
+
+/// Lines excluded from extraction by `t-cli-*` comment directives.
+#[derive(Debug, Default)]
+pub struct Directives {
+    pub ignore_file: bool,
+    ignored: Vec<RangeInclusive<usize>>,
+}
+
+impl Directives {
+    /// Read directives from comments given as `(line, text)` in source order, where `line`
+    /// is the (1-based) line the comment ends on.
+    pub fn from_comments<'a>(comments: impl IntoIterator<Item = (usize, &'a str)>) -> Self {
+        let mut directives = Self::default();
+        let mut disabled_at = None;
+        for (line, text) in comments {
+            if text.contains(IGNORE_FILE) {
+                directives.ignore_file = true;
+            } else if text.contains(IGNORE_NEXT_LINE) {
+                directives.ignored.push(line + 1..=line + 1);
+            } else if text.contains(DISABLE) {
+                disabled_at.get_or_insert(line);
+            } else if text.contains(ENABLE)
+                && let Some(start) = disabled_at.take()
+            {
+                directives.ignored.push(start..=line);
+            }
+        }
+        if let Some(start) = disabled_at {
+            directives.ignored.push(start..=usize::MAX);
+        }
+        directives
+    }
+
+    /// Treat every line of a non-JavaScript file as a potential comment.
+    pub fn from_lines(content: &str) -> Self {
+        Self::from_comments(content.lines().enumerate().map(|(i, line)| (i + 1, line)))
+    }
+
+    pub fn is_ignored(&self, line: usize) -> bool {
+        self.ignore_file || self.ignored.iter().any(|range| range.contains(&line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignore_next_line() {
+        let directives = Directives::from_comments([(2, " t-cli-ignore-next-line")]);
+        assert!(!directives.is_ignored(2));
+        assert!(directives.is_ignored(3));
+        assert!(!directives.is_ignored(4));
+    }
+
+    #[test]
+    fn disable_enable() {
+        let directives = Directives::from_comments([(2, " t-cli-disable "), (5, " t-cli-enable ")]);
+        assert!(!directives.is_ignored(1));
+        assert!((2..=5).all(|line| directives.is_ignored(line)));
+        assert!(!directives.is_ignored(6));
+    }
+
+    #[test]
+    fn unclosed_disable_runs_to_the_end() {
+        let directives = Directives::from_comments([
+            (2, " t-cli-disable "),
+            (4, " t-cli-disable "),
+            (6, " t-cli-enable "),
+            (8, " t-cli-disable "),
+        ]);
+        assert!(!directives.is_ignored(1));
+        assert!((2..=6).all(|line| directives.is_ignored(line)));
+        assert!(!directives.is_ignored(7));
+        assert!(directives.is_ignored(8));
+        assert!(directives.is_ignored(100_000));
+    }
+
+    #[test]
+    fn ignore_file_and_lines() {
+        let directives = Directives::from_lines("# title\n<!-- t-cli-ignore-file -->\n");
+        assert!(directives.ignore_file);
+        assert!(directives.is_ignored(1));
+
+        let directives = Directives::from_lines("a\n<!-- t-cli-ignore-next-line -->\nb\nc\n");
+        assert!(!directives.ignore_file);
+        assert!(directives.is_ignored(3));
+        assert!(!directives.is_ignored(4));
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+
+use swc_ecma_ast::{
+    BinaryOp, Expr, Id, Lit, MemberExpr, MemberProp, ObjectLit, Prop, PropName, PropOrSpread, Tpl,
+};
+
+/// Module-level `const` bindings by resolved identifier, used to resolve identifiers like
+/// `t(KEY)`. Parameters and locals shadowing a constant have another `SyntaxContext`.
+pub type ConstMap = HashMap<Id, Expr>;
+
+const MAX_DEPTH: usize = 32;
+const MAX_KEYS: usize = 256;
+
+/// Statically evaluate a key expression into every string it can produce:
+/// string concatenation, template literals, conditional/logical branches and
+/// `const` bindings (including `as const` object lookups like `LABELS.save`).
+/// Returns `None` when any part of the expression isn't statically known.
+pub fn expr_to_keys(expr: &Expr, consts: &ConstMap) -> Option<Vec<String>> {
+    eval(expr, consts, 0)
+}
+
+/// Like [`expr_to_keys`], but only succeeds when the expression has exactly one value.
+pub fn expr_to_key(expr: &Expr, consts: &ConstMap) -> Option<String> {
+    single(expr_to_keys(expr, consts)?)
+}
+
+/// Statically look up property `name` of an object expression (or a `const` bound to one).
+pub fn object_prop<'a>(expr: &'a Expr, name: &str, consts: &'a ConstMap) -> Option<&'a Expr> {
+    object_lit(expr, consts)?
+        .props
+        .iter()
+        .rev()
+        .find_map(|p| match p {
+            PropOrSpread::Prop(p) => match &**p {
+                Prop::KeyValue(kv) if prop_name(&kv.key)? == name => Some(&*kv.value),
+                _ => None,
+            },
+            PropOrSpread::Spread(_) => None,
+        })
+}
+
+/// Statically resolve an expression (or a `const` bound to one) to an object literal.
+pub fn object_lit<'a>(expr: &'a Expr, consts: &'a ConstMap) -> Option<&'a ObjectLit> {
+    match resolve(expr, consts, 0)? {
+        Expr::Object(obj) => Some(obj),
+        _ => None,
+    }
+}
+
+pub fn tpl_to_keys(tpl: &Tpl, consts: &ConstMap) -> Option<Vec<String>> {
+    eval_tpl(tpl, consts, 0)
+}
+
+fn eval(expr: &Expr, consts: &ConstMap, depth: usize) -> Option<Vec<String>> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    match expr {
+        Expr::Lit(Lit::Str(s)) => Some(vec![s.value.to_string()]),
+        Expr::Tpl(tpl) => eval_tpl(tpl, consts, depth),
+        Expr::Bin(bin) => match bin.op {
+            BinaryOp::Add => product(
+                eval(&bin.left, consts, depth + 1)?,
+                eval(&bin.right, consts, depth + 1)?,
+            ),
+            BinaryOp::LogicalOr | BinaryOp::NullishCoalescing => union(
+                eval(&bin.left, consts, depth + 1)?,
+                eval(&bin.right, consts, depth + 1)?,
+            ),
+            // `cond && "key"` only yields a key through its right-hand side
+            BinaryOp::LogicalAnd => eval(&bin.right, consts, depth + 1),
+            _ => None,
+        },
+        Expr::Cond(cond) => union(
+            eval(&cond.cons, consts, depth + 1)?,
+            eval(&cond.alt, consts, depth + 1)?,
+        ),
+        Expr::Ident(_) | Expr::Member(_) => {
+            let resolved = resolve(expr, consts, depth + 1)?;
+            if std::ptr::eq(resolved, expr) {
+                return None;
+            }
+            eval(resolved, consts, depth + 1)
+        }
+        _ => match unwrap(expr) {
+            Some(inner) => eval(inner, consts, depth + 1),
+            None => None,
+        },
+    }
+}
+
+fn eval_tpl(tpl: &Tpl, consts: &ConstMap, depth: usize) -> Option<Vec<String>> {
+    let mut keys = vec![String::new()];
+    for (i, quasi) in tpl.quasis.iter().enumerate() {
+        let cooked = quasi.cooked.as_ref()?;
+        keys = product(keys, vec![cooked.to_string()])?;
+        if let Some(expr) = tpl.exprs.get(i) {
+            keys = product(keys, eval(expr, consts, depth + 1)?)?;
+        }
+    }
+    Some(keys)
+}
+
+/// Follow `const` identifiers and object/array lookups down to the expression they point to.
+fn resolve<'a>(expr: &'a Expr, consts: &'a ConstMap, depth: usize) -> Option<&'a Expr> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    match expr {
+        Expr::Ident(ident) => match consts.get(&ident.to_id()) {
+            Some(init) => resolve(init, consts, depth + 1),
+            None => Some(expr),
+        },
+        Expr::Member(member) => resolve_member(member, consts, depth),
+        _ => match unwrap(expr) {
+            Some(inner) => resolve(inner, consts, depth + 1),
+            None => Some(expr),
+        },
+    }
+}
+
+fn resolve_member<'a>(
+    member: &'a MemberExpr,
+    consts: &'a ConstMap,
+    depth: usize,
+) -> Option<&'a Expr> {
+    let prop = match &member.prop {
+        MemberProp::Ident(ident) => ident.sym.to_string(),
+        MemberProp::Computed(computed) => match &*computed.expr {
+            Expr::Lit(Lit::Num(n)) => n.value.to_string(),
+            e => single(eval(e, consts, depth + 1)?)?,
+        },
+        MemberProp::PrivateName(_) => return None,
+    };
+
+    let obj = resolve(&member.obj, consts, depth + 1)?;
+    match obj {
+        Expr::Object(_) => resolve(object_prop(obj, &prop, consts)?, consts, depth + 1),
+        Expr::Array(arr) => {
+            let index: usize = prop.parse().ok()?;
+            let elem = arr.elems.get(index)?.as_ref()?;
+            if elem.spread.is_some() {
+                return None;
+            }
+            resolve(&elem.expr, consts, depth + 1)
+        }
+        _ => None,
+    }
+}
+
+fn prop_name(name: &PropName) -> Option<String> {
+    match name {
+        PropName::Ident(ident) => Some(ident.sym.to_string()),
+        PropName::Str(s) => Some(s.value.to_string()),
+        PropName::Num(n) => Some(n.value.to_string()),
+        _ => None,
+    }
+}
+
+/// Strip parentheses and TypeScript-only wrappers (`as`, `as const`, `satisfies`, `!`).
+fn unwrap(expr: &Expr) -> Option<&Expr> {
+    match expr {
+        Expr::Paren(e) => Some(&e.expr),
+        Expr::TsAs(e) => Some(&e.expr),
+        Expr::TsConstAssertion(e) => Some(&e.expr),
+        Expr::TsSatisfies(e) => Some(&e.expr),
+        Expr::TsNonNull(e) => Some(&e.expr),
+        Expr::TsTypeAssertion(e) => Some(&e.expr),
+        _ => None,
+    }
+}
+
+fn product(left: Vec<String>, right: Vec<String>) -> Option<Vec<String>> {
+    if left.len() * right.len() > MAX_KEYS {
+        return None;
+    }
+    Some(
+        left.iter()
+            .flat_map(|l| right.iter().map(move |r| format!("{l}{r}")))
+            .collect(),
+    )
+}
+
+fn union(mut left: Vec<String>, right: Vec<String>) -> Option<Vec<String>> {
+    for key in right {
+        if !left.contains(&key) {
+            left.push(key);
+        }
+    }
+    (left.len() <= MAX_KEYS).then_some(left)
+}
+
+fn single(mut keys: Vec<String>) -> Option<String> {
+    if keys.len() == 1 { keys.pop() } else { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_common::{FileName, GLOBALS, Globals, Mark, SourceMap, sync::Lrc};
+    use swc_ecma_ast::{Decl, ModuleItem, Pat, Program, Stmt, VarDeclKind};
+    use swc_ecma_parser::{Parser, StringInput, Syntax, TsSyntax};
+    use swc_ecma_transforms_base::resolver;
+    use swc_ecma_visit::VisitMutWith;
+
+    use super::*;
+
+    /// Evaluate the last expression statement of `source` against its top-level constants.
+    /// An arrow function is evaluated through its body, inside its own scope.
+    fn keys(source: &str) -> Option<Vec<String>> {
+        let cm: Lrc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(FileName::Anon.into(), source.to_string());
+        let syntax = Syntax::Typescript(TsSyntax::default());
+        let module = Parser::new(syntax, StringInput::from(&*fm), None)
+            .parse_module()
+            .unwrap();
+        let mut program = Program::Module(module);
+        GLOBALS.set(&Globals::new(), || {
+            program.visit_mut_with(&mut resolver(Mark::new(), Mark::new(), true));
+        });
+        let module = program.expect_module();
+
+        let mut consts = ConstMap::new();
+        let mut last = None;
+        for item in &module.body {
+            match item {
+                ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) if var.kind == VarDeclKind::Const => {
+                    for decl in &var.decls {
+                        if let Pat::Ident(ident) = &decl.name
+                            && let Some(init) = &decl.init
+                        {
+                            consts.insert(ident.id.to_id(), (**init).clone());
+                        }
+                    }
+                }
+                ModuleItem::Stmt(Stmt::Expr(stmt)) => last = Some(stmt.expr.clone()),
+                _ => {}
+            }
+        }
+        let expr = last.expect("expression statement");
+        match &*expr {
+            Expr::Arrow(arrow) => expr_to_keys(arrow.body.as_expr().unwrap(), &consts),
+            expr => expr_to_keys(expr, &consts),
+        }
+    }
+
+    fn strings(keys: &[&str]) -> Option<Vec<String>> {
+        Some(keys.iter().map(|k| k.to_string()).collect())
+    }
+
+    #[test]
+    fn concatenation() {
+        assert_eq!(keys(r#""Hello, " + "world";"#), strings(&["Hello, world"]));
+        assert_eq!(
+            keys(r#"const NS = "app"; `${NS}.title` + "!";"#),
+            strings(&["app.title!"])
+        );
+        assert_eq!(keys(r#""a" + b;"#), None);
+    }
+
+    #[test]
+    fn branches() {
+        assert_eq!(keys(r#"ok ? "Yes" : "No";"#), strings(&["Yes", "No"]));
+        assert_eq!(keys(r#""p." + (a ? "x" : "y");"#), strings(&["p.x", "p.y"]));
+        assert_eq!(keys(r#"a || "b" || "b";"#), None);
+        assert_eq!(keys(r#"a && "b";"#), strings(&["b"]));
+    }
+
+    #[test]
+    fn constants() {
+        assert_eq!(keys(r#"const KEY = "k"; KEY;"#), strings(&["k"]));
+        assert_eq!(
+            keys(r#"const LABELS = { save: "Save" } as const; LABELS.save;"#),
+            strings(&["Save"])
+        );
+        assert_eq!(
+            keys(r#"const LIST = ["a", "b"]; LIST[1];"#),
+            strings(&["b"])
+        );
+        assert_eq!(keys("UNKNOWN;"), None);
+    }
+
+    #[test]
+    fn shadowed_constants_are_unknown() {
+        assert_eq!(keys(r#"const KEY = "Global"; (KEY) => KEY;"#), None);
+        assert_eq!(
+            keys(r#"const LABELS = { save: "Save" }; (LABELS) => LABELS.save;"#),
+            None
+        );
+    }
+
+    #[test]
+    fn max_keys() {
+        let bit = r#"const B = c ? "0" : "1";"#;
+        assert_eq!(
+            keys(&format!("{bit} {};", ["B"; 8].join(" + ")))
+                .unwrap()
+                .len(),
+            MAX_KEYS
+        );
+        assert_eq!(keys(&format!("{bit} {};", ["B"; 9].join(" + "))), None);
+    }
+}
@@ -10,30 +10,37 @@ pub fn expr_to_value(expr: &Expr) -> Result<Value, Error> {
         Expr::Object(obj) => {
             let mut map = serde_json::Map::new();
             for prop in &obj.props {
-                if let PropOrSpread::Prop(prop_box) = prop {
-                    if let Prop::KeyValue(kv) = &**prop_box {
-                        let key = match &kv.key {
-                            PropName::Ident(ident) => ident.sym.to_string(),
-                            PropName::Str(s) => s.value.to_string(),
-                            _ => return Err(Error::Error(format!("Invalid key: {:?}", kv.key))),
-                        };
-                        let value = expr_to_value(&kv.value)?;
-                        map.insert(key, value);
-                    }
+                if let PropOrSpread::Prop(prop_box) = prop
+                    && let Prop::KeyValue(kv) = &**prop_box
+                {
+                    let key = match &kv.key {
+                        PropName::Ident(ident) => ident.sym.to_string(),
+                        PropName::Str(s) => s.value.to_string(),
+                        _ => return Err(Error::Error(format!("Invalid key: {:?}", kv.key))),
+                    };
+                    let value = expr_to_value(&kv.value)?;
+                    map.insert(key, value);
                 }
             }
             Ok(Value::Object(map))
         }
         Expr::Array(arr) => {
             let mut vec = Vec::new();
-            for elem in &arr.elems {
-                if let Some(e) = elem {
-                    vec.push(expr_to_value(&e.expr)?);
-                }
+            for e in arr.elems.iter().flatten() {
+                vec.push(expr_to_value(&e.expr)?);
             }
             Ok(Value::Array(vec))
         }
         Expr::Lit(Lit::Str(s)) => Ok(Value::String(s.value.to_string())),
+        Expr::Lit(Lit::Bool(b)) => Ok(Value::Bool(b.value)),
+        // Integral numbers stay integers so they deserialize into integer fields
+        Expr::Lit(Lit::Num(n)) if n.value.fract() == 0.0 && n.value.abs() < 2f64.powi(53) => {
+            Ok(Value::from(n.value as i64))
+        }
+        Expr::Lit(Lit::Num(n)) => Ok(serde_json::Number::from_f64(n.value)
+            .map(Value::Number)
+            .unwrap_or(Value::Null)),
+        Expr::Lit(Lit::Null(_)) => Ok(Value::Null),
         _ => Err(Error::Error(format!(
             "Unsuported expression type: {:?}",
             expr
@@ -0,0 +1,125 @@
+use std::path::Path;
+
+use globset::GlobSet;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{
+    config::{ContentFile, Extractor},
+    error::Error,
+    utils::{
+        file_filter::{glob_set, relative},
+        json_path::JsonPath,
+    },
+};
+
+/// A target's regex extractors and content files, compiled once.
+#[derive(Default)]
+pub struct Extractors {
+    regexes: Vec<(GlobSet, Vec<Regex>)>,
+    contents: Vec<(GlobSet, Vec<JsonPath>)>,
+}
+
+/// How keys are extracted from a file that isn't parsed as JavaScript.
+pub enum FileExtractor<'a> {
+    Regex(&'a [Regex]),
+    Content(&'a [JsonPath]),
+}
+
+/// A key found by a regex extractor, `offset` is the byte offset of the match.
+pub struct RegexMatch {
+    pub key: String,
+    pub context: Option<String>,
+    pub offset: usize,
+}
+
+impl Extractors {
+    pub fn new(extractors: &[Extractor], content_files: &[ContentFile]) -> Result<Self, Error> {
+        let mut regexes = vec![];
+        for extractor in extractors {
+            let mut patterns = vec![];
+            for pattern in &extractor.patterns {
+                let regex = Regex::new(pattern)?;
+                if !regex.capture_names().any(|name| name == Some("key")) {
+                    return Err(Error::Error(format!(
+                        "Extractor pattern `{pattern}` has no `key` capture group"
+                    )));
+                }
+                patterns.push(regex);
+            }
+            regexes.push((glob_set(&extractor.files)?, patterns));
+        }
+
+        let mut contents = vec![];
+        for content_file in content_files {
+            let paths = content_file
+                .paths
+                .iter()
+                .map(|path| JsonPath::parse(path))
+                .collect::<Result<_, _>>()?;
+            contents.push((glob_set(&content_file.files)?, paths));
+        }
+
+        Ok(Self { regexes, contents })
+    }
+
+    /// Extractor of the first entry whose globs match `path` (relative to the project root).
+    pub fn find(&self, path: &Path) -> Option<FileExtractor<'_>> {
+        let path = relative(path);
+        if let Some((_, patterns)) = self.regexes.iter().find(|(globs, _)| globs.is_match(path)) {
+            return Some(FileExtractor::Regex(patterns));
+        }
+        self.contents
+            .iter()
+            .find(|(globs, _)| globs.is_match(path))
+            .map(|(_, paths)| FileExtractor::Content(paths))
+    }
+}
+
+/// Every match of every pattern, in source order.
+pub fn extract_matches(patterns: &[Regex], content: &str) -> Vec<RegexMatch> {
+    let mut matches: Vec<RegexMatch> = patterns
+        .iter()
+        .flat_map(|regex| regex.captures_iter(content))
+        .filter_map(|captures| {
+            let key = captures.name("key")?;
+            Some(RegexMatch {
+                key: key.as_str().to_string(),
+                context: captures.name("context").map(|c| c.as_str().to_string()),
+                offset: key.start(),
+            })
+        })
+        .collect();
+    matches.sort_by_key(|m| m.offset);
+    matches
+}
+
+/// String values selected by `paths` in a JSON (or `.yaml`/`.yml`) document.
+pub fn extract_content(
+    path: &Path,
+    content: &str,
+    paths: &[JsonPath],
+) -> Result<Vec<String>, Error> {
+    let is_yaml = matches!(
+        path.extension().and_then(|s| s.to_str()),
+        Some("yaml" | "yml")
+    );
+    let document: Value = if is_yaml {
+        serde_yaml::from_str(content).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_str(content).map_err(|e| e.to_string())
+    }
+    .map_err(|e| Error::ParseModule(format!("{}: {e}", path.display())))?;
+
+    let mut keys = vec![];
+    for json_path in paths {
+        for value in json_path.select(&document) {
+            if let Value::String(key) = value
+                && !keys.contains(key)
+            {
+                keys.push(key.clone());
+            }
+        }
+    }
+    Ok(keys)
+}
@@ -0,0 +1,49 @@
+use std::path::Path;
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+use crate::{config::Target, error::Error};
+
+/// A target's `fileIncludes`/`fileExcludes`, matched against individual source files, and
+/// whether ignore files apply to them.
+pub struct FileFilter {
+    includes: Option<GlobSet>,
+    excludes: GlobSet,
+    pub respect_gitignore: bool,
+}
+
+impl FileFilter {
+    pub fn new(target: &Target) -> Result<Self, Error> {
+        Ok(Self {
+            includes: if target.file_includes.is_empty() {
+                None
+            } else {
+                Some(glob_set(&target.file_includes)?)
+            },
+            excludes: glob_set(&target.file_excludes)?,
+            respect_gitignore: target.respect_gitignore.unwrap_or_default(),
+        })
+    }
+
+    /// Whether `path` (relative to the project root) is included and not excluded.
+    pub fn is_match(&self, path: &Path) -> bool {
+        let path = relative(path);
+        self.includes
+            .as_ref()
+            .is_none_or(|globs| globs.is_match(path))
+            && !self.excludes.is_match(path)
+    }
+}
+
+pub fn glob_set(globs: &[String]) -> Result<GlobSet, Error> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Strip the leading `./` of paths found from the project root.
+pub fn relative(path: &Path) -> &Path {
+    path.strip_prefix(".").unwrap_or(path)
+}
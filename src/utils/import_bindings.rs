@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use swc_ecma_ast::{
+    Expr, Id, ImportDecl, ImportSpecifier, MemberProp, Module, ModuleExportName, ObjectPatProp,
+    OptChainBase, Pat, PropName, VarDeclarator,
+};
+use swc_ecma_visit::{Visit, VisitWith};
+
+use crate::config::ImportSpec;
+
+/// What a local binding refers to, relative to the configured imports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Binding {
+    /// The translation function itself
+    Function,
+    /// A factory whose result holds the translation function under this member
+    Factory(String),
+    /// An object holding the translation function under this member
+    Object(String),
+    /// A namespace import (`import * as ns`) of this module
+    Namespace(String),
+}
+
+/// Local bindings that refer to an imported translation function. Bindings are
+/// identified by `(symbol, SyntaxContext)` as assigned by swc's resolver, so a
+/// shadowing parameter or variable with the same name never matches.
+#[derive(Debug, Default)]
+pub struct ImportBindings {
+    specs: Vec<ImportSpec>,
+    bindings: HashMap<Id, Binding>,
+}
+
+impl ImportBindings {
+    /// Resolve imports of `specs` in `module`, following renames
+    /// (`const tr = t`) and destructuring (`const { t } = useTranslation()`).
+    pub fn resolve(module: &Module, specs: Vec<ImportSpec>) -> Self {
+        let mut this = Self {
+            specs,
+            bindings: HashMap::new(),
+        };
+        if this.specs.is_empty() {
+            return this;
+        }
+        // Aliases may be declared after their first use (e.g. inside hoisted functions)
+        loop {
+            let before = this.bindings.len();
+            module.visit_with(&mut this);
+            if this.bindings.len() == before {
+                break;
+            }
+        }
+        this
+    }
+
+    /// Whether `callee` refers to the imported translation function.
+    pub fn is_fn(&self, callee: &Expr) -> bool {
+        self.eval(callee) == Some(Binding::Function)
+    }
+
+    fn spec(&self, module: &str, name: &str) -> Option<Binding> {
+        self.specs
+            .iter()
+            .find(|spec| spec.module == module && spec.name == name)
+            .map(|spec| match &spec.member {
+                Some(member) => Binding::Factory(member.clone()),
+                None => Binding::Function,
+            })
+    }
+
+    fn member(&self, binding: Binding, prop: &str) -> Option<Binding> {
+        match binding {
+            Binding::Namespace(module) => self.spec(&module, prop),
+            Binding::Object(member) if member == prop => Some(Binding::Function),
+            _ => None,
+        }
+    }
+
+    fn eval(&self, expr: &Expr) -> Option<Binding> {
+        match expr {
+            Expr::Ident(ident) => self.bindings.get(&ident.to_id()).cloned(),
+            Expr::Member(member) => {
+                self.member(self.eval(&member.obj)?, &member_prop(&member.prop)?)
+            }
+            Expr::OptChain(chain) => match &*chain.base {
+                OptChainBase::Member(member) => {
+                    self.member(self.eval(&member.obj)?, &member_prop(&member.prop)?)
+                }
+                OptChainBase::Call(call) => self.call(self.eval(&call.callee)?),
+            },
+            Expr::Call(call) => self.call(self.eval(call.callee.as_expr()?)?),
+            Expr::Paren(e) => self.eval(&e.expr),
+            Expr::TsNonNull(e) => self.eval(&e.expr),
+            Expr::TsAs(e) => self.eval(&e.expr),
+            Expr::Await(e) => self.eval(&e.arg),
+            _ => None,
+        }
+    }
+
+    fn call(&self, callee: Binding) -> Option<Binding> {
+        match callee {
+            Binding::Factory(member) => Some(Binding::Object(member)),
+            _ => None,
+        }
+    }
+
+    fn bind_pat(&mut self, pat: &Pat, binding: Binding) {
+        match pat {
+            Pat::Ident(ident) => {
+                self.bindings.insert(ident.to_id(), binding);
+            }
+            Pat::Object(obj) => {
+                for prop in &obj.props {
+                    match prop {
+                        ObjectPatProp::KeyValue(kv) => {
+                            if let Some(key) = prop_name(&kv.key)
+                                && let Some(b) = self.member(binding.clone(), &key)
+                            {
+                                self.bind_pat(&kv.value, b);
+                            }
+                        }
+                        ObjectPatProp::Assign(assign) => {
+                            if let Some(b) = self.member(binding.clone(), &assign.key.sym) {
+                                self.bindings.insert(assign.key.to_id(), b);
+                            }
+                        }
+                        ObjectPatProp::Rest(_) => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Visit for ImportBindings {
+    fn visit_import_decl(&mut self, decl: &ImportDecl) {
+        if decl.type_only {
+            return;
+        }
+        let module = decl.src.value.to_string();
+        for specifier in &decl.specifiers {
+            let (local, binding) = match specifier {
+                ImportSpecifier::Named(named) => {
+                    let name = match &named.imported {
+                        Some(ModuleExportName::Ident(ident)) => ident.sym.to_string(),
+                        Some(ModuleExportName::Str(s)) => s.value.to_string(),
+                        None => named.local.sym.to_string(),
+                    };
+                    (&named.local, self.spec(&module, &name))
+                }
+                ImportSpecifier::Default(default) => {
+                    (&default.local, self.spec(&module, "default"))
+                }
+                ImportSpecifier::Namespace(ns) => {
+                    let known = self.specs.iter().any(|spec| spec.module == module);
+                    (&ns.local, known.then(|| Binding::Namespace(module.clone())))
+                }
+            };
+            if let Some(binding) = binding {
+                self.bindings.insert(local.to_id(), binding);
+            }
+        }
+    }
+
+    fn visit_var_declarator(&mut self, decl: &VarDeclarator) {
+        if let Some(init) = &decl.init
+            && let Some(binding) = self.eval(init)
+        {
+            self.bind_pat(&decl.name, binding);
+        }
+        decl.visit_children_with(self);
+    }
+}
+
+fn member_prop(prop: &MemberProp) -> Option<String> {
+    match prop {
+        MemberProp::Ident(ident) => Some(ident.sym.to_string()),
+        MemberProp::Computed(computed) => match &*computed.expr {
+            Expr::Lit(swc_ecma_ast::Lit::Str(s)) => Some(s.value.to_string()),
+            _ => None,
+        },
+        MemberProp::PrivateName(_) => None,
+    }
+}
+
+fn prop_name(name: &PropName) -> Option<String> {
+    match name {
+        PropName::Ident(ident) => Some(ident.sym.to_string()),
+        PropName::Str(s) => Some(s.value.to_string()),
+        _ => None,
+    }
+}
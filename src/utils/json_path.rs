@@ -0,0 +1,190 @@
+use serde_json::Value;
+
+use crate::error::Error;
+
+/// A JSONPath subset: `$`, `.name`, `['name']`, `[0]`, `[*]`, `.*` and `..name`.
+#[derive(Debug, Clone)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Name(String),
+    Index(usize),
+    Wildcard,
+    /// `..name`: every `name` property at any depth
+    Descendant(String),
+}
+
+impl JsonPath {
+    pub fn parse(path: &str) -> Result<Self, Error> {
+        let invalid = || Error::Error(format!("Invalid path selector `{path}`"));
+        let rest = path.strip_prefix('$').ok_or_else(invalid)?;
+        let chars: Vec<char> = rest.chars().collect();
+        let mut segments = vec![];
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '.' if chars.get(i + 1) == Some(&'.') => {
+                    let (name, end) = read_name(&chars, i + 2);
+                    if name.is_empty() {
+                        return Err(invalid());
+                    }
+                    segments.push(Segment::Descendant(name));
+                    i = end;
+                }
+                '.' if chars.get(i + 1) == Some(&'*') => {
+                    segments.push(Segment::Wildcard);
+                    i += 2;
+                }
+                '.' => {
+                    let (name, end) = read_name(&chars, i + 1);
+                    if name.is_empty() {
+                        return Err(invalid());
+                    }
+                    segments.push(Segment::Name(name));
+                    i = end;
+                }
+                '[' => {
+                    let close = chars[i..]
+                        .iter()
+                        .position(|&c| c == ']')
+                        .ok_or_else(invalid)?;
+                    let inner: String = chars[i + 1..i + close].iter().collect();
+                    let inner = inner.trim();
+                    let segment = if inner == "*" {
+                        Segment::Wildcard
+                    } else if let Ok(index) = inner.parse() {
+                        Segment::Index(index)
+                    } else if inner.len() >= 2
+                        && (inner.starts_with('\'') && inner.ends_with('\'')
+                            || inner.starts_with('"') && inner.ends_with('"'))
+                    {
+                        Segment::Name(inner[1..inner.len() - 1].to_string())
+                    } else {
+                        return Err(invalid());
+                    };
+                    segments.push(segment);
+                    i += close + 1;
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(Self { segments })
+    }
+
+    /// Every value the path selects in `root`.
+    pub fn select<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        let mut current = vec![root];
+        for segment in &self.segments {
+            let mut next = vec![];
+            for value in current {
+                match segment {
+                    Segment::Name(name) => next.extend(value.get(name)),
+                    Segment::Index(index) => next.extend(value.get(index)),
+                    Segment::Wildcard => match value {
+                        Value::Array(items) => next.extend(items),
+                        Value::Object(map) => next.extend(map.values()),
+                        _ => {}
+                    },
+                    Segment::Descendant(name) => descendants(value, name, &mut next),
+                }
+            }
+            current = next;
+        }
+        current
+    }
+}
+
+fn read_name(chars: &[char], from: usize) -> (String, usize) {
+    let end = chars[from..]
+        .iter()
+        .position(|&c| c == '.' || c == '[')
+        .map_or(chars.len(), |i| from + i);
+    (chars[from..end].iter().collect(), end)
+}
+
+fn descendants<'a>(value: &'a Value, name: &str, out: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(map) => {
+            out.extend(map.get(name));
+            for child in map.values() {
+                descendants(child, name, out);
+            }
+        }
+        Value::Array(items) => {
+            for child in items {
+                descendants(child, name, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn select(path: &str, root: &Value) -> Vec<Value> {
+        JsonPath::parse(path)
+            .unwrap()
+            .select(root)
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn names_and_indexes() {
+        let root = json!({ "meta": { "label": "Docs" }, "list": ["a", "b"] });
+        assert_eq!(JsonPath::parse("$").unwrap().select(&root), [&root]);
+        assert_eq!(select("$.meta.label", &root), [json!("Docs")]);
+        assert_eq!(select("$.list[1]", &root), [json!("b")]);
+        assert_eq!(select("$.list[2]", &root), Vec::<Value>::new());
+        assert_eq!(select("$.missing.label", &root), Vec::<Value>::new());
+    }
+
+    #[test]
+    fn quoted_names() {
+        let root = json!({ "meta": { "a.b": "dotted", "label": "Docs" } });
+        assert_eq!(select("$['meta'].label", &root), [json!("Docs")]);
+        assert_eq!(select(r#"$.meta["a.b"]"#, &root), [json!("dotted")]);
+    }
+
+    #[test]
+    fn wildcards() {
+        let root = json!({ "sections": [{ "title": "A" }, { "title": "B" }] });
+        assert_eq!(
+            select("$.sections[*].title", &root),
+            [json!("A"), json!("B")]
+        );
+        assert_eq!(
+            select("$.sections.*.title", &root),
+            [json!("A"), json!("B")]
+        );
+        assert_eq!(select("$.*", &root), [root["sections"].clone()]);
+    }
+
+    #[test]
+    fn descendant_names() {
+        let root = json!({
+            "label": "Top",
+            "nav": [{ "label": "Home", "children": [{ "label": "Sub" }] }],
+        });
+        assert_eq!(
+            select("$..label", &root),
+            [json!("Top"), json!("Home"), json!("Sub")]
+        );
+        assert_eq!(select("$.nav..label", &root), [json!("Home"), json!("Sub")]);
+    }
+
+    #[test]
+    fn invalid_selectors() {
+        for path in ["", "meta", "$.", "$..", "$[", "$[name]", "$['a]", "$x"] {
+            assert!(JsonPath::parse(path).is_err(), "{path}");
+        }
+    }
+}
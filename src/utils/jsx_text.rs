@@ -0,0 +1,47 @@
+use swc_ecma_ast::{JSXElementName, JSXObject};
+
+/// Collapse JSX text the way JSX compilers do: lines are trimmed, whitespace-only
+/// lines dropped and the remaining lines joined with a single space.
+pub fn clean_jsx_text(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let last_non_empty = lines.iter().rposition(|line| !line.trim().is_empty());
+
+    let mut result = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        let mut trimmed = line.replace('\t', " ");
+        if i != 0 {
+            trimmed = trimmed.trim_start().to_string();
+        }
+        if i != lines.len() - 1 {
+            trimmed = trimmed.trim_end().to_string();
+        }
+        if trimmed.is_empty() {
+            continue;
+        }
+        result.push_str(&trimmed);
+        if Some(i) != last_non_empty {
+            result.push(' ');
+        }
+    }
+    result
+}
+
+/// Dotted name of a JSX element, e.g. `T` or `Intl.Trans`.
+pub fn jsx_element_name(name: &JSXElementName) -> String {
+    match name {
+        JSXElementName::Ident(ident) => ident.sym.to_string(),
+        JSXElementName::JSXMemberExpr(member) => {
+            format!("{}.{}", jsx_object_name(&member.obj), member.prop.sym)
+        }
+        JSXElementName::JSXNamespacedName(ns) => format!("{}:{}", ns.ns.sym, ns.name.sym),
+    }
+}
+
+fn jsx_object_name(obj: &JSXObject) -> String {
+    match obj {
+        JSXObject::Ident(ident) => ident.sym.to_string(),
+        JSXObject::JSXMemberExpr(member) => {
+            format!("{}.{}", jsx_object_name(&member.obj), member.prop.sym)
+        }
+    }
+}
@@ -1,3 +1,13 @@
+pub mod diagnostic;
+pub mod directives;
+pub mod expr_to_keys;
 pub mod expr_to_value;
+pub mod extractors;
+pub mod file_filter;
+pub mod import_bindings;
 pub mod is_target_file;
+pub mod json_path;
+pub mod jsx_text;
 pub mod resolve;
+pub mod sfc;
+pub mod syntax;
@@ -1,58 +1,512 @@
 use std::{
-    collections::HashSet,
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
 };
 
-use ignore::{WalkBuilder, overrides::OverrideBuilder};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::{
+    DirEntry, WalkBuilder,
+    overrides::{Override, OverrideBuilder},
+};
 use path_clean::PathClean;
 
-use crate::error::Error;
+use crate::{
+    collect::GENERATED_FILE,
+    config::{Scope, Target},
+    error::Error,
+    utils::is_target_file::is_target_file,
+};
+
+/// Project-level ignore file, in `.gitignore` syntax.
+pub const IGNORE_FILE: &str = ".tcliignore";
+
+/// Marks a directory owning a dictionary with `subtree` scope.
+pub const SCOPE_FILE: &str = ".tcliscope";
 
+/// A dictionary root (its `{output}` directory holds the dictionary) and the directories
+/// whose files contribute to it, itself included.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub dir: PathBuf,
+    pub dirs: Vec<PathBuf>,
+    /// Source files inside output directories of `dirs`, which are never scanned
+    pub output_sources: Vec<PathBuf>,
+}
+
+/// Workspaces of `target`, grouped according to its `scope`. Output directories named in
+/// `outputs` hold generated files and are never scanned.
 pub fn resolve_workspaces(
     root: impl AsRef<Path>,
-    includes: &[String],
+    target: &Target,
+    outputs: &[String],
+) -> Result<Vec<Workspace>, Error> {
+    let root = root.as_ref();
+    let respect_gitignore = target.respect_gitignore.unwrap_or_default();
+    let include_paths = resolve_includes(root, target, outputs, respect_gitignore)?;
+    let negations = negations(&target.includes)?;
+    let mut workspaces: HashMap<PathBuf, HashSet<PathBuf>> = HashMap::new();
+    let mut output_sources: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+
+    for include_path in &include_paths {
+        if negations.is_match(relative_to(include_path, root)) {
+            continue;
+        }
+        let (dirs, output_dirs) = resolve_dirs(
+            root,
+            include_path,
+            &target.excludes,
+            outputs,
+            &negations,
+            respect_gitignore,
+        )?;
+        let owner = |dir: &Path| match target.scope {
+            Scope::Directory => dir.to_path_buf(),
+            Scope::Target => include_path.clone(),
+            Scope::Subtree => subtree_root(dir, include_path, target),
+        };
+        for output_dir in output_dirs {
+            let sources = sources_in(&output_dir, respect_gitignore)?;
+            if let Some(parent) = output_dir.parent()
+                && !sources.is_empty()
+            {
+                output_sources
+                    .entry(owner(parent))
+                    .or_default()
+                    .extend(sources);
+            }
+        }
+        for dir in dirs {
+            workspaces.entry(owner(&dir)).or_default().insert(dir);
+        }
+    }
+
+    let mut ws: Vec<Workspace> = workspaces
+        .into_iter()
+        .map(|(dir, dirs)| {
+            let mut dirs: Vec<PathBuf> = dirs.into_iter().collect();
+            sort_paths(&mut dirs);
+            let mut output_sources = output_sources.remove(&dir).unwrap_or_default();
+            sort_paths(&mut output_sources);
+            Workspace {
+                dir,
+                dirs,
+                output_sources,
+            }
+        })
+        .collect();
+    ws.sort_by_key(|w| w.dir.to_string_lossy().to_lowercase());
+    Ok(ws)
+}
+
+/// Include directories of `target`: literal paths (relative to `root`, a leading `/` is
+/// ignored) and globs like `packages/*/src` expanded against `root`, never inside an output
+/// directory. Includes nested in another include are dropped, and an include matching
+/// nothing is an error.
+fn resolve_includes(
+    root: &Path,
+    target: &Target,
+    outputs: &[String],
+    respect_gitignore: bool,
+) -> Result<Vec<PathBuf>, Error> {
+    let mut include_paths = vec![];
+    for include in &target.includes {
+        if include.starts_with('!') {
+            continue;
+        }
+        let pattern = clean_pattern(include);
+        let matched = if is_glob(&pattern) {
+            expand_glob(root, &pattern, &target.excludes, outputs, respect_gitignore)?
+        } else {
+            let include_path = root.join(&pattern).clean();
+            if include_path.is_dir() {
+                vec![include_path]
+            } else {
+                vec![]
+            }
+        };
+        if matched.is_empty() {
+            return Err(Error::Error(format!(
+                "Include `{include}` doesn't match any directory"
+            )));
+        }
+        include_paths.extend(matched);
+    }
+
+    sort_paths(&mut include_paths);
+    include_paths.dedup();
+    let nested: Vec<bool> = include_paths
+        .iter()
+        .map(|path| {
+            include_paths
+                .iter()
+                .any(|other| other != path && path.starts_with(other))
+        })
+        .collect();
+    let mut nested = nested.into_iter();
+    include_paths.retain(|_| !nested.next().unwrap_or_default());
+    Ok(include_paths)
+}
+
+/// Directories under `root` matching a glob, walking only below its literal prefix.
+fn expand_glob(
+    root: &Path,
+    pattern: &str,
     excludes: &[String],
+    outputs: &[String],
+    respect_gitignore: bool,
 ) -> Result<Vec<PathBuf>, Error> {
-    let root = root.as_ref();
-    let mut workspaces = HashSet::new();
+    let glob = GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()?
+        .compile_matcher();
+    let base: PathBuf = Path::new(pattern)
+        .components()
+        .take_while(|c| !is_glob(&c.as_os_str().to_string_lossy()))
+        .collect();
+    let base = root.join(base).clean();
+    if !base.is_dir() {
+        return Ok(vec![]);
+    }
 
+    let mut builder = walk_builder(&base, respect_gitignore);
+    builder.overrides(exclude_overrides(&base, excludes)?);
+    let outputs = outputs.to_vec();
+    builder.filter_entry(move |dent| !is_output_dir(dent, &outputs));
+    let mut dirs = vec![];
+    for result in builder.build() {
+        let dent = result?;
+        if dent.file_type().is_some_and(|ft| ft.is_dir())
+            && glob.is_match(relative_to(dent.path(), root))
+        {
+            dirs.push(dent.path().clean());
+        }
+    }
+    Ok(dirs)
+}
+
+/// `!pattern` includes, matched against directories relative to the project root.
+fn negations(includes: &[String]) -> Result<GlobSet, Error> {
+    let mut builder = GlobSetBuilder::new();
     for include in includes {
-        let include_cleaned = include.trim_start_matches(['/', '.', '\\']);
-        let include_path = root.join(include_cleaned).clean();
+        if let Some(negation) = include.strip_prefix('!') {
+            builder.add(
+                GlobBuilder::new(&clean_pattern(negation))
+                    .literal_separator(true)
+                    .build()?,
+            );
+        }
+    }
+    Ok(builder.build()?)
+}
 
-        if !include_path.exists() {
-            continue;
+/// Strip a leading `/` or `./` (includes are relative to the project root), keeping `..`.
+fn clean_pattern(include: &str) -> String {
+    let mut pattern = include.trim_start_matches(['/', '\\']);
+    while let Some(rest) = pattern
+        .strip_prefix("./")
+        .or_else(|| pattern.strip_prefix(".\\"))
+    {
+        pattern = rest;
+    }
+    if pattern == "." {
+        pattern = "";
+    }
+    pattern.replace('\\', "/")
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '[', '{'])
+}
+
+fn relative_to<'a>(path: &'a Path, root: &Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
+
+/// Walker shared by workspace discovery and file listing. Paths listed in `.tcliignore` files
+/// (in the walked directory or any parent) are always skipped, `.gitignore` rules only when
+/// `respect_gitignore` is set, even outside of a git repository.
+pub fn walk_builder(path: &Path, respect_gitignore: bool) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(path);
+    builder.hidden(false);
+    builder.git_ignore(respect_gitignore);
+    builder.git_exclude(respect_gitignore);
+    builder.git_global(respect_gitignore);
+    builder.require_git(false);
+    builder.parents(true);
+    builder.add_custom_ignore_filename(IGNORE_FILE);
+    builder.follow_links(false);
+    builder
+}
+
+/// Directories under `include_path` (minus `excludes`, negated includes and output
+/// directories), and the output directories that were skipped.
+fn resolve_dirs(
+    root: &Path,
+    include_path: &Path,
+    excludes: &[String],
+    outputs: &[String],
+    negations: &GlobSet,
+    respect_gitignore: bool,
+) -> Result<(Vec<PathBuf>, Vec<PathBuf>), Error> {
+    let mut builder = walk_builder(include_path, respect_gitignore);
+    // only exclude in current include
+    builder.overrides(exclude_overrides(include_path, excludes)?);
+
+    let outputs = outputs.to_vec();
+    let negations = negations.clone();
+    let root = root.to_path_buf();
+    let output_dirs = Arc::new(Mutex::new(vec![]));
+    let pruned = output_dirs.clone();
+    builder.filter_entry(move |dent| {
+        let is_dir = dent.file_type().is_some_and(|ft| ft.is_dir());
+        if !is_dir {
+            return true;
+        }
+        if negations.is_match(relative_to(dent.path(), &root)) {
+            return false;
         }
+        let is_output = is_output_dir(dent, &outputs);
+        if is_output {
+            pruned.lock().unwrap().push(dent.path().to_path_buf());
+        }
+        !is_output
+    });
 
-        let mut builder = WalkBuilder::new(&include_path);
-        builder.hidden(false);
-        builder.git_ignore(false);
-        builder.git_exclude(false);
-        builder.parents(false);
-        builder.follow_links(false);
+    let mut dirs = vec![];
+    for result in builder.build() {
+        let dent = result?;
+        if dent.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
+            dirs.push(dent.into_path());
+        }
+    }
+    let output_dirs = std::mem::take(&mut *output_dirs.lock().unwrap());
+    Ok((dirs, output_dirs))
+}
+
+/// An output directory below the walked root, pruned with its subtree.
+fn is_output_dir(dent: &DirEntry, outputs: &[String]) -> bool {
+    dent.depth() > 0
+        && dent.file_type().is_some_and(|ft| ft.is_dir())
+        && outputs.iter().any(|o| dent.path().ends_with(o))
+}
 
-        // only exclude in current include
-        let mut override_builder = OverrideBuilder::new(&include_path);
-        for pattern in excludes {
-            override_builder.add(&format!("!{pattern}"))?;
+/// Source files at any depth of an output directory, besides the generated `index.ts`.
+fn sources_in(output_dir: &Path, respect_gitignore: bool) -> Result<Vec<PathBuf>, Error> {
+    let generated = output_dir.join(GENERATED_FILE);
+    let mut sources = vec![];
+    for result in walk_builder(output_dir, respect_gitignore).build() {
+        let dent = result?;
+        let path = dent.path();
+        if dent.file_type().is_some_and(|ft| ft.is_file())
+            && is_target_file(path)
+            && path != generated
+        {
+            sources.push(dent.into_path());
         }
-        let overrides = override_builder.build()?;
-        builder.overrides(overrides);
+    }
+    Ok(sources)
+}
 
-        for result in builder.build() {
-            let dent = result?;
-            let path = dent.path();
-            if dent.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
-                workspaces.insert(path.to_path_buf());
-            }
+fn exclude_overrides(path: &Path, excludes: &[String]) -> Result<Override, Error> {
+    let mut override_builder = OverrideBuilder::new(path);
+    for pattern in excludes {
+        override_builder.add(&format!("!{pattern}"))?;
+    }
+    Ok(override_builder.build()?)
+}
+
+/// Nearest ancestor of `dir` (or itself) owning a dictionary: the include root, a directory
+/// at most `scopeDepth` levels below it, or one marked with a [`SCOPE_FILE`].
+fn subtree_root(dir: &Path, include_path: &Path, target: &Target) -> PathBuf {
+    let mut current = dir;
+    while current != include_path {
+        let depth = current
+            .strip_prefix(include_path)
+            .map_or(0, |rel| rel.components().count());
+        if depth <= target.scope_depth || current.join(SCOPE_FILE).is_file() {
+            return current.to_path_buf();
+        }
+        match current.parent() {
+            Some(parent) => current = parent,
+            None => break,
         }
     }
+    include_path.to_path_buf()
+}
+
+/// Workspaces whose directory contains `dir`, nearest first.
+pub fn ancestors<'a>(workspaces: &'a [Workspace], dir: &Path) -> Vec<&'a Workspace> {
+    let mut found: Vec<&Workspace> = workspaces
+        .iter()
+        .filter(|w| w.dir != dir && dir.starts_with(&w.dir))
+        .collect();
+    found.sort_by_key(|w| Reverse(w.dir.components().count()));
+    found
+}
 
-    let mut ws = workspaces.into_iter().collect::<Vec<_>>();
-    ws.sort_by(|a, b| {
+fn sort_paths(paths: &mut [PathBuf]) {
+    paths.sort_by(|a, b| {
         a.to_string_lossy()
             .to_lowercase()
             .cmp(&b.to_string_lossy().to_lowercase())
     });
-    Ok(ws)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn target(includes: &[&str]) -> Target {
+        serde_json::from_value(serde_json::json!({ "includes": includes, "excludes": [] })).unwrap()
+    }
+
+    fn touch(root: &Path, path: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "").unwrap();
+    }
+
+    /// Each workspace as `dir: dirs`, relative to `root`.
+    fn groups(workspaces: &[Workspace], root: &Path) -> Vec<String> {
+        let relative = |path: &Path| relative_to(path, root).to_string_lossy().replace('\\', "/");
+        workspaces
+            .iter()
+            .map(|w| {
+                let dirs: Vec<String> = w.dirs.iter().map(|dir| relative(dir)).collect();
+                format!("{}: {}", relative(&w.dir), dirs.join(", "))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn output_directories_are_skipped_and_their_sources_reported() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        for path in [
+            "src/a.ts",
+            "src/_t/index.ts",
+            "src/_t/en.json",
+            "src/_t/leak.ts",
+            "src/_t/nested/deep.tsx",
+            "src/page/b.ts",
+        ] {
+            touch(root, path);
+        }
+
+        let workspaces = resolve_workspaces(root, &target(&["src"]), &["_t".to_string()]).unwrap();
+        let dirs: Vec<&Path> = workspaces.iter().map(|w| w.dir.as_path()).collect();
+        assert_eq!(dirs, [root.join("src"), root.join("src/page")]);
+        assert_eq!(
+            workspaces[0].output_sources,
+            [
+                root.join("src/_t/leak.ts"),
+                root.join("src/_t/nested/deep.tsx")
+            ]
+        );
+        assert!(workspaces[1].output_sources.is_empty());
+    }
+
+    #[test]
+    fn glob_includes_skip_output_directories() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        for path in ["src/_t/gen.ts", "src/_t/nested/x.ts", "src/app/a.ts"] {
+            touch(root, path);
+        }
+
+        let workspaces =
+            resolve_workspaces(root, &target(&["src/**"]), &["_t".to_string()]).unwrap();
+        let dirs: Vec<&Path> = workspaces.iter().map(|w| w.dir.as_path()).collect();
+        assert_eq!(dirs, [root.join("src/app")]);
+        assert_eq!(workspaces[0].dirs, [root.join("src/app")]);
+    }
+
+    #[test]
+    fn subtree_scope_groups_into_marked_roots() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        for path in [
+            "src/a.ts",
+            "src/pages/p.ts",
+            "src/pages/home/h.ts",
+            "src/pages/home/_t/en.json",
+            "src/lib/deep/d.ts",
+            "src/lib/deep/.tcliscope",
+            "src/lib/deep/parts/x.ts",
+        ] {
+            touch(root, path);
+        }
+
+        let mut subtree = target(&["src"]);
+        subtree.scope = Scope::Subtree;
+        subtree.scope_depth = 1;
+        let workspaces = resolve_workspaces(root, &subtree, &["_t".to_string()]).unwrap();
+        assert_eq!(
+            groups(&workspaces, root),
+            [
+                "src: src",
+                "src/lib: src/lib",
+                "src/lib/deep: src/lib/deep, src/lib/deep/parts",
+                "src/pages: src/pages, src/pages/home",
+            ]
+        );
+    }
+
+    #[test]
+    fn target_scope_groups_each_include() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        for path in ["src/a.ts", "src/pages/p.ts", "lib/b.ts"] {
+            touch(root, path);
+        }
+
+        let mut per_target = target(&["src", "lib"]);
+        per_target.scope = Scope::Target;
+        let workspaces = resolve_workspaces(root, &per_target, &[]).unwrap();
+        assert_eq!(
+            groups(&workspaces, root),
+            ["lib: lib", "src: src, src/pages"]
+        );
+    }
+
+    #[test]
+    fn ancestors_nearest_first() {
+        let workspace = |dir: &str| Workspace {
+            dir: PathBuf::from(dir),
+            dirs: vec![PathBuf::from(dir)],
+            output_sources: vec![],
+        };
+        let workspaces = [
+            workspace("/app"),
+            workspace("/app/pages/home"),
+            workspace("/app/pages"),
+            workspace("/application"),
+        ];
+        let found: Vec<&Path> = ancestors(&workspaces, Path::new("/app/pages/home"))
+            .iter()
+            .map(|w| w.dir.as_path())
+            .collect();
+        assert_eq!(found, [Path::new("/app/pages"), Path::new("/app")]);
+    }
+
+    #[test]
+    fn negated_includes() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        for path in ["packages/a/src/x.ts", "packages/legacy/src/y.ts"] {
+            touch(root, path);
+        }
+
+        let negated = target(&["packages/*/src", "!packages/legacy/**"]);
+        let workspaces = resolve_workspaces(root, &negated, &[]).unwrap();
+        let dirs: Vec<&Path> = workspaces.iter().map(|w| w.dir.as_path()).collect();
+        assert_eq!(dirs, [root.join("packages/a/src")]);
+
+        let missing = resolve_workspaces(root, &target(&["apps/*"]), &[]);
+        assert!(missing.is_err());
+    }
 }
@@ -0,0 +1,578 @@
+use std::path::Path;
+
+/// JavaScript view of a single-file component (`.vue`, `.svelte`, `.astro`).
+///
+/// `<script>` blocks (and Astro frontmatter) are kept as-is and template expressions
+/// (`{{ t('x') }}`, `{t('x')}`, `:title="t('x')"`) become parenthesized statements
+/// `;(expr);`, so object literals aren't read as blocks, while Vue event handlers
+/// (`@click="save(); notify(t('x'))"`) become blocks `;{ statements }`; everything else is
+/// blanked out with spaces. Lines and columns therefore match the original file.
+pub struct SfcScript {
+    pub source: String,
+    pub typescript: bool,
+    pub jsx: bool,
+    /// `<!-- ... -->` comments outside of scripts as `(line, text)`, where `line` is the
+    /// (1-based) line the comment ends on
+    pub comments: Vec<(usize, String)>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Vue,
+    Svelte,
+    Astro,
+}
+
+fn sfc_kind(path: &Path) -> Option<Kind> {
+    match path.extension().and_then(|s| s.to_str()) {
+        Some("vue") => Some(Kind::Vue),
+        Some("svelte") => Some(Kind::Svelte),
+        Some("astro") => Some(Kind::Astro),
+        _ => None,
+    }
+}
+
+/// Split a single-file component into its script, `None` when `path` isn't one.
+pub fn sfc_to_script(path: &Path, content: &str) -> Option<SfcScript> {
+    let kind = sfc_kind(path)?;
+    let bytes = content.as_bytes();
+    let mut mask = Mask::new(content);
+
+    let mut typescript = kind == Kind::Astro;
+    let mut jsx = kind == Kind::Astro;
+    let mut start = 0;
+    if kind == Kind::Astro
+        && let Some((open_end, close_start, close_end)) = frontmatter(content)
+    {
+        mask.keep(open_end, close_start);
+        start = close_end;
+    }
+
+    let scripts = find_blocks(content, "script");
+    let styles = find_blocks(content, "style");
+    for script in &scripts {
+        mask.keep(script.content_start, script.content_end);
+        let attrs = &content[script.start + "<script".len()..script.content_start];
+        let lang = attribute(attrs, "lang").map(str::to_ascii_lowercase);
+        match lang.as_deref() {
+            Some("ts" | "typescript") => typescript = true,
+            Some("tsx") => (typescript, jsx) = (true, true),
+            Some("jsx") => jsx = true,
+            _ => {}
+        }
+    }
+
+    match kind {
+        Kind::Vue => {
+            let template = vue_template_range(content);
+            let mut blocks: Vec<(usize, usize)> = scripts
+                .iter()
+                .chain(&styles)
+                .map(|block| (block.start, block.end))
+                .chain(template)
+                .collect();
+            blocks.sort();
+            for (from, to) in blocks {
+                if from > start {
+                    html_comments(bytes, start, from, &mut mask);
+                }
+                start = start.max(to);
+            }
+            html_comments(bytes, start, bytes.len(), &mut mask);
+            if let Some((from, to)) = template {
+                vue_template(bytes, from, to, &mut mask);
+            }
+        }
+        Kind::Svelte | Kind::Astro => {
+            let mut blocks: Vec<&Block> = scripts.iter().chain(&styles).collect();
+            blocks.sort_by_key(|block| block.start);
+            for block in blocks {
+                if block.start > start {
+                    brace_template(bytes, start, block.start, &mut mask);
+                }
+                start = start.max(block.end);
+            }
+            brace_template(bytes, start, bytes.len(), &mut mask);
+        }
+    }
+
+    let comments = mask
+        .comments
+        .iter()
+        .map(|&(from, to)| {
+            let line = bytes[..to].iter().filter(|&&c| c == b'\n').count() + 1;
+            (line, content[from..to].to_string())
+        })
+        .collect();
+    Some(SfcScript {
+        source: mask.apply(content),
+        typescript,
+        jsx,
+        comments,
+    })
+}
+
+/// Value of attribute `name` in a tag's attributes, quoted or not.
+fn attribute<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = attrs.trim_end_matches(['>', '/']);
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return None;
+        }
+        let name_end = rest
+            .find(|c: char| c == '=' || c.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        let attr = &rest[..name_end];
+        rest = rest[name_end..].trim_start();
+        let Some(value) = rest.strip_prefix('=') else {
+            // Attribute without a value
+            if attr.eq_ignore_ascii_case(name) {
+                return Some("");
+            }
+            continue;
+        };
+        let value = value.trim_start();
+        let (value, after) = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let end = value[1..].find(quote)? + 1;
+                (&value[1..end], &value[end + 1..])
+            }
+            _ => {
+                let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                (&value[..end], &value[end..])
+            }
+        };
+        if attr.eq_ignore_ascii_case(name) {
+            return Some(value);
+        }
+        rest = after;
+    }
+}
+
+/// Which bytes of the original file survive, and blanked characters replaced by punctuation.
+struct Mask {
+    keep: Vec<bool>,
+    replace: Vec<Option<char>>,
+    /// Bytes starting a character (or a line break, which is never replaced)
+    replaceable: Vec<bool>,
+    /// Text ranges of the blanked `<!-- ... -->` comments
+    comments: Vec<(usize, usize)>,
+}
+
+impl Mask {
+    fn new(content: &str) -> Self {
+        let bytes = content.as_bytes();
+        Self {
+            keep: vec![false; bytes.len()],
+            replace: vec![None; bytes.len()],
+            replaceable: (0..bytes.len())
+                .map(|i| content.is_char_boundary(i) && !matches!(bytes[i], b'\n' | b'\r'))
+                .collect(),
+            comments: vec![],
+        }
+    }
+
+    fn keep(&mut self, from: usize, to: usize) {
+        self.keep[from..to].fill(true);
+    }
+
+    /// Keep `open_end..close` as a statement `;(expr);` written over its delimiters. A single
+    /// byte opening (`{`) takes its `;` from the blanked text before it; when the previous
+    /// expression ends right there, the statement stays bare (`;expr;`) so the parentheses
+    /// can't read as a call.
+    fn expr(&mut self, open: usize, open_end: usize, close: usize, close_end: usize) {
+        let separated = if open_end - open >= 2 {
+            self.replace[open_end - 2] = Some(';');
+            true
+        } else {
+            match self.blank_before(open) {
+                Some(Some(i)) => {
+                    self.replace[i] = Some(';');
+                    true
+                }
+                // Start of the file
+                Some(None) => true,
+                None => false,
+            }
+        };
+        if separated {
+            self.replace[open_end - 1] = Some('(');
+            self.replace[close] = Some(')');
+            if close_end - close >= 2 {
+                self.replace[close + 1] = Some(';');
+            }
+        } else {
+            self.replace[open_end - 1] = Some(';');
+            self.replace[close] = Some(';');
+        }
+        self.keep(open_end, close);
+    }
+
+    /// Keep `open_end..close` as a block `;{ statements }` written over its delimiters, which
+    /// needs an opening of at least two bytes.
+    fn block(&mut self, open_end: usize, close: usize) {
+        self.replace[open_end - 2] = Some(';');
+        self.replace[open_end - 1] = Some('{');
+        self.replace[close] = Some('}');
+        self.keep(open_end, close);
+    }
+
+    /// Nearest blanked character before `pos` across line breaks, `Some(None)` at the start
+    /// of the file and `None` when kept or replaced text comes first.
+    fn blank_before(&self, pos: usize) -> Option<Option<usize>> {
+        for i in (0..pos).rev() {
+            if self.keep[i] || self.replace[i].is_some() {
+                return None;
+            }
+            if self.replaceable[i] {
+                return Some(Some(i));
+            }
+        }
+        Some(None)
+    }
+
+    fn apply(&self, content: &str) -> String {
+        content
+            .char_indices()
+            .map(|(i, c)| match c {
+                _ if self.keep[i] => c,
+                '\n' | '\r' => c,
+                _ => self.replace[i].unwrap_or(' '),
+            })
+            .collect()
+    }
+}
+
+struct Block {
+    start: usize,
+    content_start: usize,
+    content_end: usize,
+    end: usize,
+}
+
+/// `<tag ...>content</tag>` blocks, matched case-insensitively.
+fn find_blocks(content: &str, tag: &str) -> Vec<Block> {
+    let lower = content.to_ascii_lowercase();
+    let open = format!("<{tag}");
+    let close = format!("</{tag}");
+    let mut blocks = vec![];
+    let mut from = 0;
+    while let Some(offset) = lower[from..].find(&open) {
+        let start = from + offset;
+        let after = start + open.len();
+        if !lower[after..].starts_with(|c: char| c.is_ascii_whitespace() || c == '>') {
+            from = after;
+            continue;
+        }
+        let Some(content_start) = lower[after..].find('>').map(|i| after + i + 1) else {
+            break;
+        };
+        let Some(content_end) = lower[content_start..]
+            .find(&close)
+            .map(|i| content_start + i)
+        else {
+            break;
+        };
+        let end = lower[content_end..]
+            .find('>')
+            .map_or(content.len(), |i| content_end + i + 1);
+        blocks.push(Block {
+            start,
+            content_start,
+            content_end,
+            end,
+        });
+        from = end;
+    }
+    blocks
+}
+
+/// Astro frontmatter: `(content start, closing fence start, closing fence end)`.
+fn frontmatter(content: &str) -> Option<(usize, usize, usize)> {
+    let trimmed = content.trim_start();
+    let offset = content.len() - trimmed.len();
+    let rest = trimmed.strip_prefix("---")?;
+    let open_end = offset + 3;
+    let close = rest.find("\n---")?;
+    let close_start = open_end + close + 1;
+    Some((open_end, close_start, close_start + 3))
+}
+
+/// Inside of the top-level `<template>`, up to the last `</template>`.
+fn vue_template_range(content: &str) -> Option<(usize, usize)> {
+    let lower = content.to_ascii_lowercase();
+    let open = lower.find("<template")?;
+    let from = open + lower[open..].find('>')? + 1;
+    let to = lower.rfind("</template")?;
+    (to >= from).then_some((from, to))
+}
+
+const VUE_EXPR_ATTRS: [&str; 7] = [
+    "v-html",
+    "v-text",
+    "v-if",
+    "v-else-if",
+    "v-show",
+    "v-model",
+    // Object syntax, `v-on="{ click: onClick }"`
+    "v-on",
+];
+
+/// `{{ expr }}` interpolations, `:attr="expr"` / `v-bind:attr="expr"` / `v-if="expr"`... and
+/// `@event="handler"` / `v-on:event="handler"`
+fn vue_template(bytes: &[u8], from: usize, to: usize, mask: &mut Mask) {
+    let mut i = from;
+    while i < to {
+        if bytes[i..].starts_with(b"<!--") {
+            i = html_comment(bytes, i, to, mask);
+        } else if bytes[i..].starts_with(b"{{")
+            && let Some(close) = scan_js(bytes, i + 2, to, b"}}")
+        {
+            mask.expr(i, i + 2, close, close + 2);
+            i = close + 2;
+        } else if bytes[i] == b'<' && bytes.get(i + 1).is_some_and(u8::is_ascii_alphabetic) {
+            i = vue_tag(bytes, i + 1, to, mask);
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Attributes of the tag starting at `i` (after `<`), returns the position after the tag.
+fn vue_tag(bytes: &[u8], mut i: usize, to: usize, mask: &mut Mask) -> usize {
+    while i < to && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' && bytes[i] != b'/' {
+        i += 1;
+    }
+    while i < to {
+        match bytes[i] {
+            b'>' => return i + 1,
+            c if c.is_ascii_whitespace() || c == b'/' => i += 1,
+            _ => {
+                let name_start = i;
+                while i < to
+                    && !matches!(bytes[i], b'=' | b'>' | b'/')
+                    && !bytes[i].is_ascii_whitespace()
+                {
+                    i += 1;
+                }
+                let name = &bytes[name_start..i];
+                while i < to && bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+                if i >= to || bytes[i] != b'=' {
+                    continue;
+                }
+                i += 1;
+                while i < to && bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+                let quote = bytes[i];
+                if quote != b'"' && quote != b'\'' {
+                    continue;
+                }
+                let Some(close) = bytes[i + 1..to].iter().position(|&c| c == quote) else {
+                    return to;
+                };
+                let close = i + 1 + close;
+                let is_expr = name.starts_with(b":")
+                    || name.starts_with(b"v-bind:")
+                    || VUE_EXPR_ATTRS.iter().any(|attr| name == attr.as_bytes());
+                let is_handler = name.starts_with(b"@") || name.starts_with(b"v-on:");
+                if is_expr {
+                    // The blanked name holds the opening `;(`
+                    mask.expr(name_start, i + 1, close, close + 1);
+                } else if is_handler {
+                    // Handlers may be statements (`count++; save()`), the name holds `;{`
+                    mask.block(i + 1, close);
+                }
+                i = close + 1;
+            }
+        }
+    }
+    to
+}
+
+/// Svelte/Astro `{expr}` expressions. Block tags (`{#if}`, `{:else}`, `{/if}`) are skipped,
+/// `{@html expr}`-style tags keep their expression.
+fn brace_template(bytes: &[u8], from: usize, to: usize, mask: &mut Mask) {
+    let mut i = from;
+    while i < to {
+        if bytes[i..].starts_with(b"<!--") {
+            i = html_comment(bytes, i, to, mask);
+            continue;
+        }
+        if bytes[i] != b'{' {
+            i += 1;
+            continue;
+        }
+        let Some(close) = scan_js(bytes, i + 1, to, b"}") else {
+            i += 1;
+            continue;
+        };
+        let mut start = i + 1;
+        while start < close && bytes[start].is_ascii_whitespace() {
+            start += 1;
+        }
+        match bytes.get(start) {
+            Some(b'#' | b':' | b'/') => {}
+            Some(b'@') => {
+                let mut word_end = start + 1;
+                while word_end < close && bytes[word_end].is_ascii_alphanumeric() {
+                    word_end += 1;
+                }
+                mask.expr(i, word_end, close, close + 1);
+            }
+            _ => mask.expr(i, i + 1, close, close + 1),
+        }
+        i = close + 1;
+    }
+}
+
+/// Record the comment starting at `i`, returns the position after it.
+fn html_comment(bytes: &[u8], i: usize, to: usize, mask: &mut Mask) -> usize {
+    let start = (i + 4).min(to);
+    let end = find(bytes, start, to, b"-->").unwrap_or(to);
+    mask.comments.push((start, end));
+    (end + 3).min(to)
+}
+
+/// `<!-- ... -->` comments between a Vue component's top-level blocks.
+fn html_comments(bytes: &[u8], from: usize, to: usize, mask: &mut Mask) {
+    let mut i = from;
+    while let Some(start) = find(bytes, i, to, b"<!--") {
+        i = html_comment(bytes, start, to, mask);
+    }
+}
+
+/// Position of `terminator` at bracket depth 0, skipping over strings and template literals.
+fn scan_js(bytes: &[u8], from: usize, to: usize, terminator: &[u8]) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut i = from;
+    while i < to {
+        if depth == 0 && bytes[i..to].starts_with(terminator) {
+            return Some(i);
+        }
+        match bytes[i] {
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < to && bytes[i] != quote && bytes[i] != b'\n' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b'`' => {
+                i += 1;
+                while i < to && bytes[i] != b'`' {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    } else if bytes[i..].starts_with(b"${") {
+                        i = scan_js(bytes, i + 2, to, b"}")?;
+                    }
+                    i += 1;
+                }
+            }
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth = depth.checked_sub(1)?,
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+fn find(bytes: &[u8], from: usize, to: usize, needle: &[u8]) -> Option<usize> {
+    bytes[from.min(to)..to]
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|i| from + i)
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_common::{FileName, SourceMap, sync::Lrc};
+    use swc_ecma_parser::{Parser, StringInput, Syntax, TsSyntax};
+
+    use super::*;
+
+    /// Masked source of a component, checked to parse as a module.
+    fn script(name: &str, content: &str) -> String {
+        let sfc = sfc_to_script(Path::new(name), content).unwrap();
+        let cm: Lrc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(FileName::Anon.into(), sfc.source.clone());
+        let syntax = Syntax::Typescript(TsSyntax {
+            tsx: sfc.jsx,
+            ..Default::default()
+        });
+        if let Err(e) = Parser::new(syntax, StringInput::from(&*fm), None).parse_module() {
+            panic!("{:?} in:\n{}", e.kind(), sfc.source);
+        }
+        assert_eq!(sfc.source.lines().count(), content.lines().count());
+        sfc.source
+    }
+
+    #[test]
+    fn script_lang() {
+        let lang = |attrs: &str| {
+            let sfc =
+                sfc_to_script(Path::new("App.vue"), &format!("<script{attrs}></script>")).unwrap();
+            (sfc.typescript, sfc.jsx)
+        };
+        assert_eq!(lang(""), (false, false));
+        assert_eq!(lang(r#" setup lang="ts""#), (true, false));
+        assert_eq!(lang(" lang='typescript'"), (true, false));
+        assert_eq!(lang(" lang=TS setup"), (true, false));
+        assert_eq!(lang(r#" lang = "tsx""#), (true, true));
+        assert_eq!(lang(r#" lang="jsx""#), (false, true));
+        assert_eq!(lang(r#" data-lang="ts""#), (false, false));
+        assert_eq!(lang(r#" lang="ts" setup"#), (true, false));
+        assert_eq!(lang(" setup"), (false, false));
+    }
+
+    #[test]
+    fn vue_object_bindings() {
+        let source = script(
+            "App.vue",
+            r#"<template><div :class="{ 'is-active': active }" v-bind:style="{ color: c, fontSize: s }">{{ t('x') }}</div></template>"#,
+        );
+        assert_eq!(
+            source,
+            r#"                     ;({ 'is-active': active })             ;({ color: c, fontSize: s }) ;( t('x') );                 "#
+        );
+    }
+
+    #[test]
+    fn vue_event_handlers() {
+        let source = script(
+            "App.vue",
+            r#"<template><button @click="notify(t('saved'))" v-on:keyup.enter="n++; save(t('x'))" @input="() => t('y')" v-on="{ blur: onBlur }">{{ t('z') }}</button></template>"#,
+        );
+        assert_eq!(
+            source,
+            r#"                        ;{notify(t('saved'))}                 ;{n++; save(t('x'))}       ;{() => t('y')}     ;({ blur: onBlur }) ;( t('z') );                    "#
+        );
+    }
+
+    #[test]
+    fn astro_frontmatter_and_objects() {
+        let source = script(
+            "Page.astro",
+            "---\nconst title = t('title');\n---\n<p style={{ color: 'red', margin: 0 }}>{t('x')}</p>\n",
+        );
+        assert_eq!(
+            source,
+            "   \nconst title = t('title');\n   \n        ;({ color: 'red', margin: 0 });(t('x'))    \n"
+        );
+    }
+
+    #[test]
+    fn svelte_blocks() {
+        let source = script(
+            "App.svelte",
+            "<script>\nlet ok = true;\n</script>\n{#if ok}{t('a')}{:else}{t('b')}{/if}\n{a}{b}\n{@html t('c')}\n",
+        );
+        assert_eq!(
+            source,
+            "        \nlet ok = true;\n         \n       ;(t('a'))      ;(t('b'))    ;\n(a);b;\n    ;( t('c'))\n"
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use std::path::Path;
+
+use swc_ecma_parser::{EsSyntax, Syntax, TsSyntax};
+
+use crate::config::SyntaxOptions;
+
+/// Parser syntax for a source file, picked from its extension:
+/// `.ts/.mts/.cts` are TypeScript without JSX (so `<Foo>bar` casts parse), `.tsx` is
+/// TypeScript with JSX and `.js/.jsx/.mjs/.cjs` are ECMAScript with JSX.
+/// Any option set on the target overrides the extension's default.
+pub fn syntax_for_path(path: &Path, options: &SyntaxOptions) -> Syntax {
+    let ext = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let (typescript, jsx) = match ext {
+        "ts" | "mts" | "cts" => (true, false),
+        "tsx" => (true, true),
+        _ => (false, true),
+    };
+    syntax_with_defaults(path, typescript, jsx, options)
+}
+
+/// Like [`syntax_for_path`], with the TypeScript/JSX defaults supplied by the caller
+/// (e.g. from a single-file component's `<script lang="ts">`).
+pub fn syntax_with_defaults(
+    path: &Path,
+    typescript: bool,
+    jsx: bool,
+    options: &SyntaxOptions,
+) -> Syntax {
+    let typescript = options.typescript.unwrap_or(typescript);
+    let jsx = options.jsx.unwrap_or(jsx);
+    let decorators = options.decorators.unwrap_or(true);
+
+    if typescript {
+        Syntax::Typescript(TsSyntax {
+            tsx: jsx,
+            decorators,
+            dts: path.to_string_lossy().ends_with(".d.ts"),
+            ..Default::default()
+        })
+    } else {
+        Syntax::Es(EsSyntax {
+            jsx,
+            decorators,
+            ..Default::default()
+        })
+    }
+}